@@ -0,0 +1,268 @@
+use crate::database::{get_all_lottery_results, get_lottery_by_date};
+use crate::integrity::is_valid_number;
+use crate::types::{Category, LotteryData};
+use crate::utils::parse_draw_date;
+use chrono::Datelike;
+use rusqlite::{Connection, Result};
+use serde::Serialize;
+use serde_json::{Map, Value, json};
+use std::collections::{BTreeMap, HashMap};
+
+/// How many stored draws had each last-2 prize, indexed by the ending 00-99.
+/// Prizes that are not two-digit numbers are left out.
+pub fn last2_counts(conn: &Connection) -> Result<[u32; 100]> {
+    let mut counts = [0u32; 100];
+    for data in get_all_lottery_results(conn)? {
+        if let Some(index) = data.last_two_digits.as_deref().and_then(|n| n.parse::<usize>().ok())
+            && index < 100
+        {
+            counts[index] += 1;
+        }
+    }
+    Ok(counts)
+}
+
+/// Counts each two-digit ending among stored last-2 prizes and the chi-square
+/// statistic against a uniform expectation (99 degrees of freedom).
+///
+/// This is descriptive only: with a few hundred draws large deviations are
+/// expected by chance, and it says nothing about how GLO conducts its draws.
+pub fn last2_uniformity(conn: &Connection) -> Result<Value> {
+    let counts = last2_counts(conn)?;
+    let total: u32 = counts.iter().sum();
+
+    let expected = total as f64 / 100.0;
+    let chi_square = if total == 0 {
+        0.0
+    } else {
+        counts
+            .iter()
+            .map(|&observed| (observed as f64 - expected).powi(2) / expected)
+            .sum()
+    };
+
+    let mut per_ending = Map::new();
+    for (ending, count) in counts.iter().enumerate() {
+        per_ending.insert(format!("{:02}", ending), json!(count));
+    }
+
+    Ok(json!({
+        "draws": total,
+        "expected_per_ending": expected,
+        "counts": per_ending,
+        "chi_square": chi_square,
+        "degrees_of_freedom": 99,
+    }))
+}
+
+/// For each digit position of the category's numbers, how often each digit
+/// 0-9 appeared. Numbers of the wrong length for the category are ignored.
+pub fn digit_position_frequency(conn: &Connection, category: Category) -> Result<Vec<[u32; 10]>> {
+    let mut frequency = vec![[0u32; 10]; category.digits()];
+    for data in get_all_lottery_results(conn)? {
+        for number in data.numbers(category) {
+            if number.len() != category.digits() {
+                continue;
+            }
+            for (position, digit) in number.chars().enumerate() {
+                if let Some(digit) = digit.to_digit(10) {
+                    frequency[position][digit as usize] += 1;
+                }
+            }
+        }
+    }
+    Ok(frequency)
+}
+
+/// How many stored draws fell on each weekday, Monday first. Dates that do
+/// not parse are left out.
+pub fn draw_weekday_distribution(conn: &Connection) -> Result<[u32; 7]> {
+    let mut counts = [0u32; 7];
+    for data in get_all_lottery_results(conn)? {
+        if let Ok(date) = parse_draw_date(&data.draw_date) {
+            counts[date.weekday().num_days_from_monday() as usize] += 1;
+        }
+    }
+    Ok(counts)
+}
+
+/// For every possible 2- or 3-digit ending, the last draw it won as the last-2
+/// or last-3 prize and how many draws ago that was (0 = the latest draw).
+/// Endings that never won count as overdue by every stored draw. Sorted most
+/// overdue first.
+pub fn overdue_numbers(conn: &Connection, digits: u32) -> Result<Vec<(String, Option<String>, u32)>> {
+    let category = match digits {
+        2 => Category::Last2,
+        3 => Category::Last3,
+        _ => {
+            return Err(rusqlite::Error::ToSqlConversionFailure(
+                format!("Invalid digits {}: expected 2 or 3", digits).into(),
+            ));
+        }
+    };
+
+    // Newest first, so the first sighting of an ending is its latest.
+    let results = get_all_lottery_results(conn)?;
+    let mut last_seen: HashMap<String, (String, u32)> = HashMap::new();
+    for (draws_ago, data) in results.iter().enumerate() {
+        for number in data.numbers(category) {
+            last_seen
+                .entry(number)
+                .or_insert_with(|| (data.draw_date.clone(), draws_ago as u32));
+        }
+    }
+
+    let mut overdue: Vec<(String, Option<String>, u32)> = (0..10u32.pow(digits))
+        .map(|n| {
+            let ending = format!("{:0width$}", n, width = digits as usize);
+            match last_seen.remove(&ending) {
+                Some((draw_date, draws_ago)) => (ending, Some(draw_date), draws_ago),
+                None => (ending, None, results.len() as u32),
+            }
+        })
+        .collect();
+    overdue.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.0.cmp(&b.0)));
+    Ok(overdue)
+}
+
+#[derive(Serialize, Debug)]
+pub struct DueScore {
+    pub number: String,
+    pub category: Category,
+    /// Draws between wins if every number of the category were equally likely.
+    pub expected_interval: f64,
+    /// Mean draws between the number's stored wins; needs at least two wins.
+    pub average_interval: Option<f64>,
+    /// Draws since the number last won (0 = the latest draw), or `None` if it
+    /// never has.
+    pub current_gap: Option<usize>,
+    pub appearances: usize,
+}
+
+/// How a number's history in one category compares with chance: the interval
+/// a uniform draw would give, the interval actually seen, and the current gap.
+/// Like `last2_uniformity`, this describes the past and predicts nothing.
+pub fn number_due_score(conn: &Connection, number: &str, category: Category) -> Result<DueScore> {
+    if !is_valid_number(category, number) {
+        return Err(rusqlite::Error::ToSqlConversionFailure(
+            format!("Invalid number {}: {} numbers have {} digits", number, category, category.digits()).into(),
+        ));
+    }
+
+    // Oldest first, so positions are draw indexes in time order.
+    let mut results = get_all_lottery_results(conn)?;
+    results.reverse();
+    let wins: Vec<usize> = results
+        .iter()
+        .enumerate()
+        .filter(|(_, data)| data.numbers(category).iter().any(|n| n == number))
+        .map(|(index, _)| index)
+        .collect();
+
+    let average_interval = match (wins.first(), wins.last()) {
+        (Some(first), Some(last)) if wins.len() > 1 => Some((last - first) as f64 / (wins.len() - 1) as f64),
+        _ => None,
+    };
+    Ok(DueScore {
+        number: number.to_string(),
+        category,
+        expected_interval: 10f64.powi(category.digits() as i32) / category.expected_count() as f64,
+        average_interval,
+        current_gap: wins.last().map(|last| results.len() - 1 - last),
+        appearances: wins.len(),
+    })
+}
+
+#[derive(Serialize, Debug)]
+pub struct EndingMatch {
+    pub category: Category,
+    pub number: String,
+    /// The last-2 or last-3 prize the number ends in.
+    pub prize_category: Category,
+    pub ending: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct SharedEnding {
+    pub ending: String,
+    /// `(category, number)` for every six-digit prize ending in `ending`; always
+    /// spans more than one category.
+    pub numbers: Vec<(Category, String)>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Cooccurrence {
+    pub draw_date: String,
+    /// Six-digit prizes whose ending is also the draw's last-2 or last-3 prize.
+    pub ending_matches: Vec<EndingMatch>,
+    /// Two-digit endings shared by six-digit prizes of different categories.
+    pub shared_endings: Vec<SharedEnding>,
+}
+
+const SIX_DIGIT_CATEGORIES: [Category; 6] =
+    [Category::First, Category::Near1, Category::Second, Category::Third, Category::Fourth, Category::Fifth];
+
+/// Digit patterns within one draw that players look for, such as the last-2
+/// prize matching the end of the first prize. `None` if no draw is stored.
+pub fn cooccurrence(conn: &Connection, date: &str) -> Result<Option<Cooccurrence>> {
+    let Some(data) = get_lottery_by_date(conn, date)? else {
+        return Ok(None);
+    };
+
+    let mut ending_matches = Vec::new();
+    let mut by_ending: BTreeMap<String, Vec<(Category, String)>> = BTreeMap::new();
+    for category in SIX_DIGIT_CATEGORIES {
+        for number in data.numbers(category) {
+            for prize_category in [Category::Last2, Category::Last3] {
+                for ending in data.numbers(prize_category) {
+                    if number.ends_with(&ending) {
+                        ending_matches.push(EndingMatch { category, number: number.clone(), prize_category, ending });
+                    }
+                }
+            }
+            if let Some(ending) = number.get(number.len().saturating_sub(2)..) {
+                by_ending.entry(ending.to_string()).or_default().push((category, number.clone()));
+            }
+        }
+    }
+
+    let shared_endings = by_ending
+        .into_iter()
+        .filter(|(_, numbers)| numbers.iter().any(|(category, _)| *category != numbers[0].0))
+        .map(|(ending, numbers)| SharedEnding { ending, numbers })
+        .collect();
+    Ok(Some(Cooccurrence { draw_date: data.draw_date, ending_matches, shared_endings }))
+}
+
+#[derive(Serialize, Debug)]
+pub struct SharedNumber {
+    pub number: String,
+    pub category_a: Category,
+    pub category_b: Category,
+}
+
+#[derive(Serialize, Debug)]
+pub struct DrawComparison {
+    pub draw_a: Option<LotteryData>,
+    pub draw_b: Option<LotteryData>,
+    /// Numbers present in both draws, in any category.
+    pub shared: Vec<SharedNumber>,
+}
+
+pub fn compare_draws(conn: &Connection, date_a: &str, date_b: &str) -> Result<DrawComparison> {
+    let draw_a = get_lottery_by_date(conn, date_a)?;
+    let draw_b = get_lottery_by_date(conn, date_b)?;
+
+    let mut shared = Vec::new();
+    if let (Some(a), Some(b)) = (&draw_a, &draw_b) {
+        for (category_a, numbers_a) in a.prize_categories() {
+            for (category_b, numbers_b) in b.prize_categories() {
+                for number in numbers_a.iter().filter(|n| numbers_b.contains(n)) {
+                    shared.push(SharedNumber { number: number.clone(), category_a, category_b });
+                }
+            }
+        }
+    }
+
+    Ok(DrawComparison { draw_a, draw_b, shared })
+}
@@ -0,0 +1,366 @@
+use crate::config::Config;
+use crate::database::{
+    get_lottery_by_date, import_lottery_result_with_raw, is_known_empty_date, mark_empty_draw_date,
+};
+use crate::types::{LotteryData, LotteryRequest, LotteryResponse};
+use crate::source::LotterySource;
+use crate::utils::{draw_date_year, format_draw_date, generate_lottery_dates, parse_draw_date, split_draw_date};
+use chrono::NaiveDate;
+use futures::stream::{self, StreamExt};
+use rusqlite::Connection;
+use std::error::Error;
+use std::time::Duration;
+
+/// What happened to each requested date in a batch fetch, by `YYYY-MM-DD`.
+#[derive(Debug, Default)]
+pub struct FetchSummary {
+    pub saved: Vec<String>,
+    pub already_stored: Vec<String>,
+    /// GLO answered that there was no draw on these past dates.
+    pub no_draw: Vec<String>,
+    /// No result yet for dates from `today` on. These are not recorded as
+    /// drawless, so a later fetch asks again.
+    pub not_yet_drawn: Vec<String>,
+    /// Dates recorded as drawless by an earlier fetch, so not requested.
+    pub skipped: Vec<String>,
+    /// `(date, error)` for network and parse failures; re-run to retry.
+    pub failed: Vec<(String, String)>,
+}
+
+pub const GLO_RESULT_URL: &str = "https://www.glo.or.th/api/checking/getLotteryResult";
+
+/// The GLO response body for a draw, unparsed. A request still running after
+/// `timeout` fails; see `is_timeout`.
+pub async fn fetch_lottery_json(date: &str, month: &str, year: &str, timeout: Duration) -> Result<String, Box<dyn Error>> {
+    let client = reqwest::Client::builder().timeout(timeout).build()?;
+    let request_body = LotteryRequest {
+        date: date.to_string(),
+        month: month.to_string(),
+        year: year.to_string(),
+    };
+    
+    let response = client
+        .post(GLO_RESULT_URL)
+        .header("Content-Type", "application/json")
+        .json(&request_body)
+        .send()
+        .await?;
+    
+    Ok(response.text().await?)
+}
+
+/// Whether a fetch error is the request timing out.
+pub fn is_timeout(error: &(dyn Error + 'static)) -> bool {
+    error.downcast_ref::<reqwest::Error>().is_some_and(|e| e.is_timeout())
+}
+
+pub async fn fetch_lottery_result(
+    date: &str,
+    month: &str,
+    year: &str,
+    timeout: Duration,
+) -> Result<LotteryResponse, Box<dyn Error>> {
+    let lottery_response: LotteryResponse = serde_json::from_str(&fetch_lottery_json(date, month, year, timeout).await?)?;
+    Ok(lottery_response)
+}
+
+/// `fetch_lottery_result` for a `YYYY-MM-DD` date.
+pub async fn fetch_lottery_result_by_date(draw_date: &str, timeout: Duration) -> Result<LotteryResponse, Box<dyn Error>> {
+    let (date, month, year) = split_draw_date(draw_date)?;
+    fetch_lottery_result(&date, &month, &year, timeout).await
+}
+
+/// Like `fetch_lottery_result`, but returns `Ok(None)` when GLO answers that
+/// there was no draw on the date, so only network and parse failures are errors.
+/// The draw comes with the raw payload it was parsed from.
+pub async fn fetch_draw(
+    date: &str,
+    month: &str,
+    year: &str,
+    timeout: Duration,
+) -> Result<Option<(LotteryData, String)>, Box<dyn Error>> {
+    parse_draw_response(fetch_lottery_json(date, month, year, timeout).await?)
+}
+
+/// Parses a GLO response body into the draw and the body itself, or `None`
+/// when GLO answered that there was no draw.
+pub fn parse_draw_response(raw_json: String) -> Result<Option<(LotteryData, String)>, Box<dyn Error>> {
+    let response: LotteryResponse = serde_json::from_str(&raw_json)?;
+    match response.data {
+        Some(data) if response.is_success() => Ok(Some((data, raw_json))),
+        _ => Ok(None),
+    }
+}
+
+#[derive(Debug)]
+pub enum SingleFetchOutcome {
+    Saved(Box<LotteryData>),
+    /// The draw was already stored, so GLO was not asked again.
+    AlreadyStored,
+    NoDraw,
+    /// No result yet for a date that is today or later; nothing was recorded.
+    NotYetDrawn,
+}
+
+/// Whether a no-draw answer for `draw_date` is final. GLO has no result yet
+/// for today's draw or later ones, which must not be recorded as drawless.
+fn is_past_draw(draw_date: &str, today: NaiveDate) -> bool {
+    parse_draw_date(draw_date).is_ok_and(|date| date < today)
+}
+
+/// The draw for one `YYYY-MM-DD` date from `source`, without saving it or
+/// recording an empty date. `None` if there was no draw.
+pub async fn fetch_lottery_preview(source: &dyn LotterySource, draw_date: &str) -> Result<Option<LotteryData>, Box<dyn Error>> {
+    let (date, month, year) = split_draw_date(draw_date)?;
+    Ok(source.fetch(&date, &month, &year).await?.map(|(data, _)| data))
+}
+
+/// Fetches and saves the draw for one `(date, month, year)`. Unlike the batch
+/// fetch, a date earlier recorded as drawless is requested again. A date
+/// before `today` without a draw is recorded as drawless.
+pub async fn fetch_and_save_single_result(
+    conn: &Connection,
+    source: &dyn LotterySource,
+    date: &str,
+    month: &str,
+    year: &str,
+    today: NaiveDate,
+) -> Result<SingleFetchOutcome, Box<dyn Error>> {
+    let draw_date = format_draw_date(date, month, year);
+    if get_lottery_by_date(conn, &draw_date)?.is_some() {
+        return Ok(SingleFetchOutcome::AlreadyStored);
+    }
+    match source.fetch(date, month, year).await? {
+        Some((data, raw_json)) => {
+            import_lottery_result_with_raw(conn, &data, &raw_json)?;
+            Ok(SingleFetchOutcome::Saved(Box::new(data)))
+        }
+        None if is_past_draw(&draw_date, today) => {
+            mark_empty_draw_date(conn, &draw_date)?;
+            Ok(SingleFetchOutcome::NoDraw)
+        }
+        None => Ok(SingleFetchOutcome::NotYetDrawn),
+    }
+}
+
+/// `fetch_and_save_single_result` for a `YYYY-MM-DD` date.
+pub async fn fetch_and_save_result_by_date(
+    conn: &Connection,
+    source: &dyn LotterySource,
+    draw_date: &str,
+    today: NaiveDate,
+) -> Result<SingleFetchOutcome, Box<dyn Error>> {
+    let (date, month, year) = split_draw_date(draw_date)?;
+    fetch_and_save_single_result(conn, source, &date, &month, &year, today).await
+}
+
+/// Fetches the scheduled draws between two `YYYY-MM-DD` dates (inclusive)
+/// that are not stored yet, as `fetch_and_save_multiple_results` does.
+pub async fn fetch_and_save_range(
+    conn: &Connection,
+    source: &dyn LotterySource,
+    start_date: &str,
+    end_date: &str,
+    config: &Config,
+    today: NaiveDate,
+) -> Result<FetchSummary, Box<dyn Error>> {
+    let (start_year, end_year) = (draw_date_year(start_date)?, draw_date_year(end_date)?);
+    let mut dates = Vec::new();
+    for year in start_year..=end_year {
+        for (date, month, year) in generate_lottery_dates(&year.to_string()) {
+            let draw_date = format_draw_date(&date, &month, &year);
+            if draw_date.as_str() >= start_date
+                && draw_date.as_str() <= end_date
+                && get_lottery_by_date(conn, &draw_date)?.is_none()
+            {
+                dates.push((date, month, year));
+            }
+        }
+    }
+    fetch_and_save_multiple_results(conn, source, &dates, config, today).await
+}
+
+/// Fetches and saves a batch of `(date, month, year)` triples from `source`
+/// with at most `config.fetch_concurrency` requests in flight, each slot
+/// pausing for `config.request_delay` after a network request so GLO is not
+/// hammered. Results are saved on the calling task as they arrive. Only dates
+/// before `today` are recorded as drawless when GLO has no result for them.
+pub async fn fetch_and_save_multiple_results(
+    conn: &Connection,
+    source: &dyn LotterySource,
+    dates: &[(String, String, String)],
+    config: &Config,
+    today: NaiveDate,
+) -> Result<FetchSummary, Box<dyn Error>> {
+    let mut summary = FetchSummary::default();
+    let mut pending = Vec::new();
+    for (date, month, year) in dates {
+        let draw_date = format_draw_date(date, month, year);
+        if is_known_empty_date(conn, &draw_date)? {
+            summary.skipped.push(draw_date);
+        } else {
+            pending.push((draw_date, date, month, year));
+        }
+    }
+
+    let mut results = stream::iter(pending)
+        .map(|(draw_date, date, month, year)| async move {
+            let result = source.fetch(date, month, year).await.map_err(|e| {
+                if is_timeout(e.as_ref()) { "timeout".to_string() } else { e.to_string() }
+            });
+            if source.is_rate_limited() {
+                tokio::time::sleep(config.request_delay).await;
+            }
+            (draw_date, result)
+        })
+        .buffer_unordered(config.fetch_concurrency.max(1));
+
+    while let Some((draw_date, result)) = results.next().await {
+        match result {
+            Ok(Some((data, raw_json))) => {
+                if import_lottery_result_with_raw(conn, &data, &raw_json)?.was_new {
+                    summary.saved.push(draw_date);
+                } else {
+                    summary.already_stored.push(draw_date);
+                }
+            }
+            Ok(None) if is_past_draw(&draw_date, today) => {
+                mark_empty_draw_date(conn, &draw_date)?;
+                summary.no_draw.push(draw_date);
+            }
+            Ok(None) => summary.not_yet_drawn.push(draw_date),
+            Err(e) => summary.failed.push((draw_date, e)),
+        }
+    }
+
+    summary.saved.sort();
+    summary.already_stored.sort();
+    summary.no_draw.sort();
+    summary.not_yet_drawn.sort();
+    summary.failed.sort();
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::create_in_memory_database;
+    use crate::source::FileSource;
+    use async_trait::async_trait;
+
+    /// Answers "no draw" for every date.
+    struct NoDrawSource;
+
+    #[async_trait(?Send)]
+    impl LotterySource for NoDrawSource {
+        async fn fetch(&self, _: &str, _: &str, _: &str) -> Result<Option<(LotteryData, String)>, Box<dyn Error>> {
+            Ok(None)
+        }
+
+        fn is_rate_limited(&self) -> bool {
+            false
+        }
+    }
+
+    fn triple(date: &str, month: &str, year: &str) -> (String, String, String) {
+        (date.to_string(), month.to_string(), year.to_string())
+    }
+
+    #[tokio::test]
+    async fn only_past_dates_without_a_draw_are_recorded() {
+        let conn = create_in_memory_database().unwrap();
+        let today = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        let dates = [triple("01", "10", "2026"), triple("16", "10", "2026"), triple("01", "11", "2026")];
+
+        let summary = fetch_and_save_multiple_results(&conn, &NoDrawSource, &dates, &Config::default(), today)
+            .await
+            .unwrap();
+
+        assert_eq!(summary.no_draw, ["2026-10-01"]);
+        assert_eq!(summary.not_yet_drawn, ["2026-10-16", "2026-11-01"]);
+        assert!(is_known_empty_date(&conn, "2026-10-01").unwrap());
+        assert!(!is_known_empty_date(&conn, "2026-10-16").unwrap());
+        assert!(!is_known_empty_date(&conn, "2026-11-01").unwrap());
+    }
+
+    #[tokio::test]
+    async fn single_fetch_does_not_record_a_future_date() {
+        let conn = create_in_memory_database().unwrap();
+        let today = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+
+        let outcome = fetch_and_save_result_by_date(&conn, &NoDrawSource, "2026-11-01", today).await.unwrap();
+
+        assert!(matches!(outcome, SingleFetchOutcome::NotYetDrawn));
+        assert!(!is_known_empty_date(&conn, "2026-11-01").unwrap());
+    }
+
+    /// Has a draw on the 1st, no draw on the 16th, and fails on any other day.
+    struct StubSource;
+
+    #[async_trait(?Send)]
+    impl LotterySource for StubSource {
+        async fn fetch(&self, date: &str, month: &str, year: &str) -> Result<Option<(LotteryData, String)>, Box<dyn Error>> {
+            let draw_date = format_draw_date(date, month, year);
+            match date {
+                "01" => Ok(Some((sample_draw(&draw_date), String::from("{}")))),
+                "16" => Ok(None),
+                _ => Err("connection reset".into()),
+            }
+        }
+
+        fn is_rate_limited(&self) -> bool {
+            false
+        }
+    }
+
+    fn sample_draw(draw_date: &str) -> LotteryData {
+        serde_json::from_value(serde_json::json!({ "drawDate": draw_date, "drawNo": "1", "first": "123456" })).unwrap()
+    }
+
+    #[tokio::test]
+    async fn batch_fetch_sorts_saved_drawless_and_failed_dates() {
+        let conn = create_in_memory_database().unwrap();
+        let today = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        let dates = [triple("01", "03", "2024"), triple("16", "03", "2024"), triple("02", "04", "2024")];
+
+        let summary = fetch_and_save_multiple_results(&conn, &StubSource, &dates, &Config::default(), today)
+            .await
+            .unwrap();
+
+        assert_eq!(summary.saved, ["2024-03-01"]);
+        assert_eq!(summary.no_draw, ["2024-03-16"]);
+        assert_eq!(summary.failed, [("2024-04-02".to_string(), "connection reset".to_string())]);
+        assert!(get_lottery_by_date(&conn, "2024-03-01").unwrap().is_some());
+        let empty: Vec<String> = conn
+            .prepare("SELECT draw_date FROM empty_draw_dates")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(empty, ["2024-03-16"]);
+    }
+
+    #[tokio::test]
+    async fn missing_saved_response_fails_instead_of_being_recorded() {
+        let conn = create_in_memory_database().unwrap();
+        let today = NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        let dir = std::env::temp_dir().join(format!("lotto-source-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let saved = serde_json::json!({ "status": "success", "data": sample_draw("2024-03-01") });
+        std::fs::write(dir.join("2024-03-01.json"), saved.to_string()).unwrap();
+        let source = FileSource { dir: dir.clone() };
+        let dates = [triple("01", "03", "2024"), triple("16", "03", "2024")];
+
+        let summary = fetch_and_save_multiple_results(&conn, &source, &dates, &Config::default(), today)
+            .await
+            .unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(summary.saved, ["2024-03-01"]);
+        assert!(summary.no_draw.is_empty());
+        assert_eq!(summary.failed.len(), 1);
+        assert_eq!(summary.failed[0].0, "2024-03-16");
+        assert!(!is_known_empty_date(&conn, "2024-03-16").unwrap());
+    }
+}
@@ -0,0 +1,104 @@
+use std::env;
+use std::time::Duration;
+
+pub const DEFAULT_FETCH_CONCURRENCY: usize = 2;
+pub const DEFAULT_REQUEST_DELAY_MS: u64 = 1000;
+pub const DEFAULT_REQUEST_TIMEOUT_SECS: u64 = 30;
+pub const DEFAULT_RESULTS_LIMIT: usize = 10;
+pub const DEFAULT_MAX_RESULTS: usize = 500;
+
+/// Runtime settings, read from the environment by `Config::load`.
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// How many GLO requests may be in flight at once (`LOTTERY_FETCH_CONCURRENCY`).
+    pub fetch_concurrency: usize,
+    /// Pause after each request before its slot is reused (`LOTTERY_REQUEST_DELAY_MS`).
+    pub request_delay: Duration,
+    /// Longest a GLO request may take before it fails (`LOTTERY_REQUEST_TIMEOUT_SECS`).
+    pub request_timeout: Duration,
+    /// Upper bound on rows returned by list queries (`LOTTERY_MAX_RESULTS`).
+    pub max_results: usize,
+    /// Open the database read-only and refuse commands that write (`LOTTERY_READ_ONLY`).
+    pub read_only: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            fetch_concurrency: DEFAULT_FETCH_CONCURRENCY,
+            request_delay: Duration::from_millis(DEFAULT_REQUEST_DELAY_MS),
+            request_timeout: Duration::from_secs(DEFAULT_REQUEST_TIMEOUT_SECS),
+            max_results: DEFAULT_MAX_RESULTS,
+            read_only: false,
+        }
+    }
+}
+
+fn env_parse<T: std::str::FromStr>(name: &str) -> Option<T> {
+    env::var(name).ok().and_then(|value| value.parse().ok())
+}
+
+/// A boolean setting: `1/0`, `true/false`, `yes/no` or `on/off`, any case.
+/// Any other non-empty value counts as true, so a typo never quietly leaves
+/// a safeguard such as `LOTTERY_READ_ONLY` switched off.
+fn parse_flag(value: &str) -> Option<bool> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "" => None,
+        "0" | "false" | "no" | "off" => Some(false),
+        _ => Some(true),
+    }
+}
+
+fn env_flag(name: &str) -> Option<bool> {
+    env::var(name).ok().as_deref().and_then(parse_flag)
+}
+
+impl Config {
+    /// Defaults overridden by any valid `LOTTERY_*` environment variables.
+    pub fn load() -> Config {
+        let defaults = Config::default();
+        Config {
+            fetch_concurrency: env_parse::<usize>("LOTTERY_FETCH_CONCURRENCY")
+                .filter(|&n| n > 0)
+                .unwrap_or(defaults.fetch_concurrency),
+            request_delay: env_parse("LOTTERY_REQUEST_DELAY_MS")
+                .map(Duration::from_millis)
+                .unwrap_or(defaults.request_delay),
+            request_timeout: env_parse::<u64>("LOTTERY_REQUEST_TIMEOUT_SECS")
+                .filter(|&n| n > 0)
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.request_timeout),
+            max_results: env_parse::<usize>("LOTTERY_MAX_RESULTS")
+                .filter(|&n| n > 0)
+                .unwrap_or(defaults.max_results),
+            read_only: env_flag("LOTTERY_READ_ONLY").unwrap_or(defaults.read_only),
+        }
+    }
+
+    /// A requested row limit made safe: non-positive means the default and
+    /// anything above `max_results` is capped. The flag is true when capped.
+    pub fn clamp_limit(&self, requested: i32) -> (usize, bool) {
+        if requested <= 0 {
+            return (DEFAULT_RESULTS_LIMIT.min(self.max_results), false);
+        }
+        let requested = requested as usize;
+        (requested.min(self.max_results), requested > self.max_results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_accept_common_spellings_and_fail_safe() {
+        for value in ["1", "true", "YES", "On"] {
+            assert_eq!(parse_flag(value), Some(true), "{}", value);
+        }
+        for value in ["0", "False", "no", "off"] {
+            assert_eq!(parse_flag(value), Some(false), "{}", value);
+        }
+        assert_eq!(parse_flag("enabled"), Some(true));
+        assert_eq!(parse_flag(""), None);
+    }
+}
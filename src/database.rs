@@ -0,0 +1,725 @@
+use crate::migrations::apply_migrations;
+use crate::types::{Category, LotteryData, MatchMode, SortOrder};
+use crate::utils::{
+    adjacent_numbers, format_draw_date, generate_lottery_dates, last_day_of_month,
+    latest_scheduled_draw, parse_created_at, parse_draw_date,
+};
+use chrono::{DateTime, NaiveDate, Utc};
+use rusqlite::{Connection, OpenFlags, OptionalExtension, Result, ToSql, params_from_iter};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::env;
+use std::error::Error;
+use std::io::Write;
+use std::path::Path;
+
+const SELECT_COLUMNS: &str = "draw_date, draw_no, first_prize, last_two_digits, last_three_digits,
+            near_first, second_prize, third_prize, fourth_prize, fifth_prize";
+
+pub const DEFAULT_DB_PATH: &str = "lottery.db";
+
+pub struct DatabaseStats {
+    pub total_draws: i64,
+    pub first_draw: Option<String>,
+    pub last_draw: Option<String>,
+}
+
+/// The database path from `LOTTERY_DB_PATH`, falling back to `lottery.db`.
+pub fn default_db_path() -> String {
+    env::var("LOTTERY_DB_PATH").unwrap_or_else(|_| DEFAULT_DB_PATH.to_string())
+}
+
+pub struct ImportOutcome {
+    pub lottery_id: i64,
+    /// False when the draw date was already stored and nothing was written.
+    pub was_new: bool,
+    pub prize_numbers_inserted: usize,
+}
+
+pub fn create_database(path: &str) -> Result<Connection> {
+    let conn = Connection::open(path)?;
+    create_database_with_connection(&conn)?;
+    Ok(conn)
+}
+
+/// Opens an existing database without write access and without migrating it,
+/// so any attempted write fails.
+pub fn open_read_only(path: &str) -> Result<Connection> {
+    Connection::open_with_flags(
+        path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX | OpenFlags::SQLITE_OPEN_URI,
+    )
+}
+
+/// A migrated database that lives only as long as the connection, for tests
+/// and throwaway work. `create_database(":memory:")` is equivalent.
+pub fn create_in_memory_database() -> Result<Connection> {
+    let conn = Connection::open_in_memory()?;
+    create_database_with_connection(&conn)?;
+    Ok(conn)
+}
+
+/// Brings an already-open connection's schema up to date.
+pub fn create_database_with_connection(conn: &Connection) -> Result<()> {
+    apply_migrations(conn)?;
+    Ok(())
+}
+
+pub fn save_lottery_result(conn: &Connection, data: &LotteryData) -> Result<()> {
+    conn.execute(
+        "INSERT INTO lottery_results (
+            draw_date, draw_no, first_prize, last_two_digits, last_three_digits,
+            near_first, second_prize, third_prize, fourth_prize, fifth_prize, created_at
+        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, strftime('%Y-%m-%dT%H:%M:%SZ', 'now'))",
+        (
+            &data.draw_date,
+            &data.draw_no,
+            &data.first_prize,
+            &data.last_two_digits,
+            &data.last_three_digits.as_ref().map(|v| v.join(",")),
+            &data.near_first.as_ref().map(|v| v.join(",")),
+            &data.second_prize.as_ref().map(|v| v.join(",")),
+            &data.third_prize.as_ref().map(|v| v.join(",")),
+            &data.fourth_prize.as_ref().map(|v| v.join(",")),
+            &data.fifth_prize.as_ref().map(|v| v.join(",")),
+        ),
+    )?;
+    Ok(())
+}
+
+/// When the draw on `draw_date` was stored, in UTC, or `None` if no draw is
+/// stored. Rows written before timestamps were RFC 3339 are read as UTC too.
+pub fn get_created_at(conn: &Connection, draw_date: &str) -> Result<Option<DateTime<Utc>>> {
+    let created_at: Option<String> = conn
+        .query_row(
+            "SELECT created_at FROM lottery_results WHERE draw_date = ?1 ORDER BY id DESC LIMIT 1",
+            [draw_date],
+            |row| row.get(0),
+        )
+        .optional()?;
+    created_at
+        .map(|created_at| {
+            parse_created_at(&created_at).ok_or_else(|| {
+                rusqlite::Error::FromSqlConversionFailure(
+                    0,
+                    rusqlite::types::Type::Text,
+                    format!("Invalid created_at {:?}", created_at).into(),
+                )
+            })
+        })
+        .transpose()
+}
+
+/// Saves a draw unless its date is already stored, reporting which happened.
+pub fn import_lottery_result(conn: &Connection, data: &LotteryData) -> Result<ImportOutcome> {
+    let existing: Option<i64> = conn
+        .query_row(
+            "SELECT id FROM lottery_results WHERE draw_date = ?1 ORDER BY id LIMIT 1",
+            [&data.draw_date],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if let Some(lottery_id) = existing {
+        return Ok(ImportOutcome { lottery_id, was_new: false, prize_numbers_inserted: 0 });
+    }
+
+    save_lottery_result(conn, data)?;
+    Ok(ImportOutcome {
+        lottery_id: conn.last_insert_rowid(),
+        was_new: true,
+        prize_numbers_inserted: data.prize_categories().iter().map(|(_, numbers)| numbers.len()).sum(),
+    })
+}
+
+/// `import_lottery_result` that also keeps the GLO payload the draw was parsed
+/// from. The payload is added to an already-stored draw that has none.
+pub fn import_lottery_result_with_raw(conn: &Connection, data: &LotteryData, raw_json: &str) -> Result<ImportOutcome> {
+    let outcome = import_lottery_result(conn, data)?;
+    conn.execute(
+        "UPDATE lottery_results SET raw_json = ?1 WHERE id = ?2 AND raw_json IS NULL",
+        (raw_json, outcome.lottery_id),
+    )?;
+    Ok(outcome)
+}
+
+/// The original GLO payload stored for a draw, if it was kept.
+pub fn get_raw_json(conn: &Connection, draw_date: &str) -> Result<Option<String>> {
+    conn.query_row(
+        "SELECT raw_json FROM lottery_results WHERE draw_date = ?1 ORDER BY id DESC LIMIT 1",
+        [draw_date],
+        |row| row.get(0),
+    )
+    .optional()
+    .map(Option::flatten)
+}
+
+/// Records a date GLO reported as having no draw, so later fetches skip it.
+pub fn mark_empty_draw_date(conn: &Connection, draw_date: &str) -> Result<()> {
+    conn.execute(
+        "INSERT OR IGNORE INTO empty_draw_dates (draw_date) VALUES (?1)",
+        [draw_date],
+    )?;
+    Ok(())
+}
+
+pub fn is_known_empty_date(conn: &Connection, draw_date: &str) -> Result<bool> {
+    conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM empty_draw_dates WHERE draw_date = ?1)",
+        [draw_date],
+        |row| row.get(0),
+    )
+}
+
+pub fn get_all_lottery_results(conn: &Connection) -> Result<Vec<LotteryData>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM lottery_results ORDER BY draw_date DESC",
+        SELECT_COLUMNS
+    ))?;
+    let rows = stmt.query_map([], LotteryData::from_row)?;
+    rows.collect()
+}
+
+/// The `limit` most recent draws, newest first.
+pub fn get_latest_lottery_results(conn: &Connection, limit: usize) -> Result<Vec<LotteryData>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM lottery_results ORDER BY draw_date DESC LIMIT ?1",
+        SELECT_COLUMNS
+    ))?;
+    let rows = stmt.query_map([limit as i64], LotteryData::from_row)?;
+    rows.collect()
+}
+
+/// The `n`th most recent stored draw, where 1 is the latest, or `None` if
+/// fewer than `n` draws are stored or `n` is 0.
+pub fn get_nth_latest(conn: &Connection, n: usize) -> Result<Option<LotteryData>> {
+    let Some(offset) = n.checked_sub(1) else {
+        return Ok(None);
+    };
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM lottery_results ORDER BY draw_date DESC LIMIT 1 OFFSET ?1",
+        SELECT_COLUMNS
+    ))?;
+    let mut rows = stmt.query_map([offset as i64], LotteryData::from_row)?;
+    rows.next().transpose()
+}
+
+fn for_each_row<E, F>(conn: &Connection, sql: &str, params: &[&dyn ToSql], mut f: F) -> Result<(), E>
+where
+    E: From<rusqlite::Error>,
+    F: FnMut(LotteryData) -> Result<(), E>,
+{
+    let mut stmt = conn.prepare(sql)?;
+    let mut rows = stmt.query(params)?;
+    while let Some(row) = rows.next()? {
+        f(LotteryData::from_row(row)?)?;
+    }
+    Ok(())
+}
+
+/// Calls `f` with every stored draw, oldest first, one row at a time rather
+/// than collecting the table into memory.
+pub fn for_each_lottery_result<E, F>(conn: &Connection, f: F) -> Result<(), E>
+where
+    E: From<rusqlite::Error>,
+    F: FnMut(LotteryData) -> Result<(), E>,
+{
+    let sql = format!("SELECT {} FROM lottery_results ORDER BY draw_date ASC", SELECT_COLUMNS);
+    for_each_row(conn, &sql, &[], f)
+}
+
+pub fn for_each_lottery_result_in_range<E, F>(conn: &Connection, start_date: &str, end_date: &str, f: F) -> Result<(), E>
+where
+    E: From<rusqlite::Error>,
+    F: FnMut(LotteryData) -> Result<(), E>,
+{
+    let sql = format!(
+        "SELECT {} FROM lottery_results WHERE draw_date BETWEEN ?1 AND ?2 ORDER BY draw_date ASC",
+        SELECT_COLUMNS
+    );
+    for_each_row(conn, &sql, &[&start_date, &end_date], f)
+}
+
+/// The draws stored between two dates (inclusive), sorted by draw date in `order`.
+pub fn get_lottery_results_by_date_range(
+    conn: &Connection,
+    start_date: &str,
+    end_date: &str,
+    order: SortOrder,
+) -> Result<Vec<LotteryData>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM lottery_results WHERE draw_date BETWEEN ?1 AND ?2 ORDER BY draw_date {}",
+        SELECT_COLUMNS,
+        order.as_sql()
+    ))?;
+    let rows = stmt.query_map([start_date, end_date], LotteryData::from_row)?;
+    rows.collect()
+}
+
+pub fn get_lottery_results_by_month(conn: &Connection, year: i32, month: u32, order: SortOrder) -> Result<Vec<LotteryData>> {
+    let end_date = last_day_of_month(year, month).ok_or_else(|| {
+        rusqlite::Error::ToSqlConversionFailure(format!("Invalid month {}-{}", year, month).into())
+    })?;
+    let start_date = format!("{}-{:02}-01", year, month);
+    get_lottery_results_by_date_range(conn, &start_date, &end_date.format("%Y-%m-%d").to_string(), order)
+}
+
+pub fn get_lottery_by_date(conn: &Connection, draw_date: &str) -> Result<Option<LotteryData>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM lottery_results WHERE draw_date = ?1 ORDER BY id DESC LIMIT 1",
+        SELECT_COLUMNS
+    ))?;
+    let mut rows = stmt.query_map([draw_date], LotteryData::from_row)?;
+    rows.next().transpose()
+}
+
+/// The draw stored for each of `dates`, in the order asked, with `None` for
+/// dates that have none. Reads all of them in one query.
+pub fn get_lottery_by_dates(conn: &Connection, dates: &[String]) -> Result<Vec<Option<LotteryData>>> {
+    if dates.is_empty() {
+        return Ok(Vec::new());
+    }
+    let placeholders = vec!["?"; dates.len()].join(", ");
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM lottery_results WHERE draw_date IN ({}) ORDER BY id",
+        SELECT_COLUMNS, placeholders
+    ))?;
+    // Later rows win, matching get_lottery_by_date's newest-id choice.
+    let mut by_date: HashMap<String, LotteryData> = HashMap::new();
+    for data in stmt.query_map(params_from_iter(dates), LotteryData::from_row)? {
+        let data = data?;
+        by_date.insert(data.draw_date.clone(), data);
+    }
+    Ok(dates.iter().map(|date| by_date.get(date).cloned()).collect())
+}
+
+fn winning_categories(data: &LotteryData, number: &str) -> Vec<Category> {
+    data.prize_categories()
+        .into_iter()
+        .filter(|(_, numbers)| numbers.iter().any(|n| n == number))
+        .map(|(category, _)| category)
+        .collect()
+}
+
+/// Returns `(draw_date, category)` for every prize the number won, newest first.
+pub fn search_number(conn: &Connection, number: &str) -> Result<Vec<(String, String)>> {
+    let mut matches = Vec::new();
+    for data in get_all_lottery_results(conn)? {
+        for category in winning_categories(&data, number) {
+            matches.push((data.draw_date.clone(), category.to_string()));
+        }
+    }
+    Ok(matches)
+}
+
+pub struct SearchPage {
+    /// `(draw_date, category)`, newest first.
+    pub matches: Vec<(String, String)>,
+    /// Matches across all pages.
+    pub total: usize,
+}
+
+/// One page of `search_number`: up to `limit` matches after skipping `offset`.
+pub fn search_number_page(conn: &Connection, number: &str, limit: usize, offset: usize) -> Result<SearchPage> {
+    let all = search_number(conn, number)?;
+    let total = all.len();
+    Ok(SearchPage { matches: all.into_iter().skip(offset).take(limit).collect(), total })
+}
+
+/// Whether `number` is one of the two numbers adjacent to the first prize on
+/// `date`, worked out from the first prize rather than the stored near-first
+/// list. `None` if no draw with a first prize is stored for the date.
+pub fn is_near_first(conn: &Connection, date: &str, number: &str) -> Result<Option<bool>> {
+    let first_prize = get_lottery_by_date(conn, date)?.and_then(|data| data.first_prize);
+    Ok(first_prize.map(|first| {
+        adjacent_numbers(&first).is_some_and(|(below, above)| number == below || number == above)
+    }))
+}
+
+/// The latest scheduled draw date as of `today` and whether it is stored.
+pub fn check_for_new_draw(conn: &Connection, today: NaiveDate) -> Result<(String, bool)> {
+    let expected = latest_scheduled_draw(today).format("%Y-%m-%d").to_string();
+    let stored = get_lottery_by_date(conn, &expected)?.is_some();
+    Ok((expected, stored))
+}
+
+/// The dates, oldest first, on which the number matched a prize in each
+/// category, in category order. Categories it never matched are left out.
+pub fn number_timeline(conn: &Connection, number: &str, mode: MatchMode) -> Result<Vec<(Category, Vec<String>)>> {
+    let mut timeline: BTreeMap<Category, Vec<String>> = BTreeMap::new();
+    for_each_lottery_result(conn, |data| {
+        for (category, numbers) in data.prize_categories() {
+            if numbers.iter().any(|n| mode.matches(n, number)) {
+                timeline.entry(category).or_default().push(data.draw_date.clone());
+            }
+        }
+        Ok::<_, rusqlite::Error>(())
+    })?;
+    Ok(timeline.into_iter().collect())
+}
+
+/// Attaches the database file at `path` read-only as `alias`. A missing file
+/// is an error rather than being created empty, as a plain `ATTACH` would.
+fn attach_read_only(conn: &Connection, path: &str, alias: &str) -> Result<()> {
+    if !Path::new(path).is_file() {
+        return Err(rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CANTOPEN),
+            Some(format!("No database file at {}", path)),
+        ));
+    }
+    let escaped = path.replace('%', "%25").replace('?', "%3f").replace('#', "%23");
+    conn.execute(&format!("ATTACH DATABASE ?1 AS {}", alias), [format!("file:{}?mode=ro", escaped)])?;
+    Ok(())
+}
+
+/// `search_number` across the primary database and other lottery databases,
+/// attached for the duration of one `UNION ALL` query. Returns
+/// `(source, draw_date, category)` where source is `main` or the other path.
+pub fn attach_and_search(primary: &Connection, other_paths: &[&str], number: &str) -> Result<Vec<(String, String, String)>> {
+    let mut attached: Vec<(String, String)> = vec![("main".to_string(), "main".to_string())];
+    let mut result = Ok(Vec::new());
+    for (i, path) in other_paths.iter().enumerate() {
+        let alias = format!("other{}", i);
+        if let Err(e) = attach_read_only(primary, path, &alias) {
+            result = Err(e);
+            break;
+        }
+        attached.push((alias, path.to_string()));
+    }
+
+    if result.is_ok() {
+        result = search_attached(primary, &attached, number);
+    }
+
+    for (alias, _) in attached.iter().skip(1) {
+        primary.execute(&format!("DETACH DATABASE {}", alias), [])?;
+    }
+    result
+}
+
+fn search_attached(conn: &Connection, sources: &[(String, String)], number: &str) -> Result<Vec<(String, String, String)>> {
+    // The source index goes last so LotteryData::from_row can read columns 0..10.
+    let sql = sources
+        .iter()
+        .enumerate()
+        .map(|(i, (alias, _))| format!("SELECT {}, {} FROM {}.lottery_results", SELECT_COLUMNS, i, alias))
+        .collect::<Vec<_>>()
+        .join(" UNION ALL ");
+    let mut stmt = conn.prepare(&format!("{} ORDER BY draw_date DESC", sql))?;
+    let mut rows = stmt.query([])?;
+
+    let mut matches = Vec::new();
+    while let Some(row) = rows.next()? {
+        let data = LotteryData::from_row(row)?;
+        let source: usize = row.get(10)?;
+        for category in winning_categories(&data, number) {
+            matches.push((sources[source].1.clone(), data.draw_date.clone(), category.to_string()));
+        }
+    }
+    Ok(matches)
+}
+
+#[derive(Debug, Default)]
+pub struct DbDiff {
+    pub only_in_primary: Vec<String>,
+    pub only_in_other: Vec<String>,
+    /// Dates stored in both whose draw number or prize numbers differ.
+    pub differing: Vec<String>,
+}
+
+/// Compares the draws in `primary` with another database file, attached for
+/// the duration of the comparison. Dates are sorted oldest first.
+pub fn diff_databases(primary: &Connection, other_path: &str) -> Result<DbDiff> {
+    attach_read_only(primary, other_path, "other")?;
+    let result = diff_attached(primary);
+    primary.execute("DETACH DATABASE other", [])?;
+    result
+}
+
+fn diff_attached(conn: &Connection) -> Result<DbDiff> {
+    let dates = |sql: &str| -> Result<Vec<String>> {
+        let mut stmt = conn.prepare(sql)?;
+        let rows = stmt.query_map([], |row| row.get(0))?;
+        rows.collect()
+    };
+    let compared = SELECT_COLUMNS
+        .split(',')
+        .map(str::trim)
+        .filter(|column| *column != "draw_date")
+        .map(|column| format!("a.{0} IS NOT b.{0}", column))
+        .collect::<Vec<_>>()
+        .join(" OR ");
+
+    Ok(DbDiff {
+        only_in_primary: dates(
+            "SELECT DISTINCT draw_date FROM main.lottery_results
+             WHERE draw_date NOT IN (SELECT draw_date FROM other.lottery_results) ORDER BY draw_date",
+        )?,
+        only_in_other: dates(
+            "SELECT DISTINCT draw_date FROM other.lottery_results
+             WHERE draw_date NOT IN (SELECT draw_date FROM main.lottery_results) ORDER BY draw_date",
+        )?,
+        differing: dates(&format!(
+            "SELECT DISTINCT a.draw_date FROM main.lottery_results a
+             JOIN other.lottery_results b ON a.draw_date = b.draw_date
+             WHERE {} ORDER BY a.draw_date",
+            compared
+        ))?,
+    })
+}
+
+pub fn get_database_stats(conn: &Connection) -> Result<DatabaseStats> {
+    conn.query_row(
+        "SELECT COUNT(DISTINCT draw_date), MIN(draw_date), MAX(draw_date) FROM lottery_results",
+        [],
+        |row| {
+            Ok(DatabaseStats {
+                total_draws: row.get(0)?,
+                first_draw: row.get(1)?,
+                last_draw: row.get(2)?,
+            })
+        },
+    )
+}
+
+/// Distinct digit lengths seen in each category. A clean database has exactly
+/// one length per category.
+pub fn number_lengths_by_category(conn: &Connection) -> Result<Vec<(String, Vec<i32>)>> {
+    let mut lengths: BTreeMap<Category, BTreeSet<i32>> = BTreeMap::new();
+    for data in get_all_lottery_results(conn)? {
+        for (category, numbers) in data.prize_categories() {
+            for number in numbers {
+                lengths.entry(category).or_default().insert(number.len() as i32);
+            }
+        }
+    }
+    Ok(lengths
+        .into_iter()
+        .map(|(category, lengths)| (category.to_string(), lengths.into_iter().collect()))
+        .collect())
+}
+
+/// Days between `today` and the latest draw in which the number won exactly,
+/// or `None` if it never has. Callers pass the date so it can be pinned.
+pub fn days_since_number(conn: &Connection, number: &str, today: NaiveDate) -> Result<Option<i64>> {
+    let last_seen = search_number(conn, number)?
+        .iter()
+        .filter_map(|(draw_date, _)| parse_draw_date(draw_date).ok())
+        .max();
+    Ok(last_seen.map(|date| (today - date).num_days()))
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+const WIDE_CSV_HEADER: &str = "draw_date,draw_no,first,last2,last3\n";
+
+fn wide_csv_row(data: &LotteryData) -> String {
+    let fields = [
+        data.draw_date.clone(),
+        data.draw_no.clone(),
+        data.first_prize.clone().unwrap_or_default(),
+        data.last_two_digits.clone().unwrap_or_default(),
+        data.last_three_digits.as_ref().map(|v| v.join(",")).unwrap_or_default(),
+    ];
+    let row: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+    format!("{}\n", row.join(","))
+}
+
+/// Writes one CSV row per draw between the two dates, with the headline
+/// prizes in columns, as each row is read. Multi-number categories are
+/// comma-joined within a quoted cell.
+pub fn write_draws_wide_csv<W: Write>(conn: &Connection, start_date: &str, end_date: &str, writer: &mut W) -> Result<(), Box<dyn Error>> {
+    writer.write_all(WIDE_CSV_HEADER.as_bytes())?;
+    for_each_lottery_result_in_range(conn, start_date, end_date, |data| {
+        writer.write_all(wide_csv_row(&data).as_bytes())?;
+        Ok::<_, Box<dyn Error>>(())
+    })?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Deletes every draw between the two dates (inclusive) in one transaction and
+/// returns how many were removed.
+pub fn delete_range(conn: &Connection, start_date: &str, end_date: &str) -> Result<usize> {
+    let tx = conn.unchecked_transaction()?;
+    let deleted = tx.execute(
+        "DELETE FROM lottery_results WHERE draw_date BETWEEN ?1 AND ?2",
+        [start_date, end_date],
+    )?;
+    tx.commit()?;
+    Ok(deleted)
+}
+
+/// `(draw_date, first_prize)` for every first prize containing `substring`.
+pub fn first_prizes_containing(conn: &Connection, substring: &str) -> Result<Vec<(String, String)>> {
+    let escaped = substring.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+    let mut stmt = conn.prepare(
+        "SELECT draw_date, first_prize FROM lottery_results
+         WHERE first_prize LIKE ?1 ESCAPE '\\'
+         ORDER BY draw_date DESC",
+    )?;
+    let rows = stmt.query_map([format!("%{}%", escaped)], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    rows.collect()
+}
+
+/// `(draw_date, first_prize)` for the numerically lowest and highest first
+/// prizes stored, earliest draw first on ties, or `None` for an empty database.
+pub fn first_prize_extremes(conn: &Connection) -> Result<Option<[(String, String); 2]>> {
+    let extreme = |order: SortOrder| -> Result<Option<(String, String)>> {
+        conn.query_row(
+            &format!(
+                "SELECT draw_date, first_prize FROM lottery_results
+                 WHERE first_prize IS NOT NULL AND first_prize NOT GLOB '*[^0-9]*' AND first_prize <> ''
+                 ORDER BY CAST(first_prize AS INTEGER) {}, draw_date ASC LIMIT 1",
+                order.as_sql()
+            ),
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+    };
+    match (extreme(SortOrder::Asc)?, extreme(SortOrder::Desc)?) {
+        (Some(lowest), Some(highest)) => Ok(Some([lowest, highest])),
+        _ => Ok(None),
+    }
+}
+
+/// `(distinct first prizes, draws with a first prize)`.
+pub fn first_prize_uniqueness(conn: &Connection) -> Result<(i64, i64)> {
+    conn.query_row(
+        "SELECT COUNT(DISTINCT first_prize), COUNT(DISTINCT draw_date)
+         FROM lottery_results WHERE first_prize IS NOT NULL",
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )
+}
+
+/// `(draw_date, prize numbers stored)` per draw, sorted by the count. Low counts
+/// usually mean an incomplete import.
+pub fn draws_by_prize_count(conn: &Connection, order: SortOrder, limit: i32) -> Result<Vec<(String, i64)>> {
+    let mut counts: Vec<(String, i64)> = get_all_lottery_results(conn)?
+        .into_iter()
+        .map(|data| {
+            let count = data.prize_categories().iter().map(|(_, numbers)| numbers.len() as i64).sum();
+            (data.draw_date, count)
+        })
+        .collect();
+    match order {
+        SortOrder::Asc => counts.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(&b.0))),
+        SortOrder::Desc => counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| b.0.cmp(&a.0))),
+    }
+    counts.truncate(limit.max(0) as usize);
+    Ok(counts)
+}
+
+/// Mean number of stored prize numbers per draw, or 0.0 for an empty database.
+pub fn avg_prizes_per_draw(conn: &Connection) -> Result<f64> {
+    let results = get_all_lottery_results(conn)?;
+    if results.is_empty() {
+        return Ok(0.0);
+    }
+    let total: usize = results
+        .iter()
+        .flat_map(|data| data.prize_categories())
+        .map(|(_, numbers)| numbers.len())
+        .sum();
+    Ok(total as f64 / results.len() as f64)
+}
+
+/// The nearest stored draw dates before and after `date`, which itself need
+/// not be stored.
+pub fn neighbors(conn: &Connection, date: &str) -> Result<(Option<String>, Option<String>)> {
+    conn.query_row(
+        "SELECT
+            (SELECT MAX(draw_date) FROM lottery_results WHERE draw_date < ?1),
+            (SELECT MIN(draw_date) FROM lottery_results WHERE draw_date > ?1)",
+        [date],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )
+}
+
+/// Every scheduled draw date of the year paired with whether it is stored.
+pub fn year_coverage_map(conn: &Connection, year: &str) -> Result<Vec<(String, bool)>> {
+    let mut stmt = conn.prepare(
+        "SELECT DISTINCT draw_date FROM lottery_results WHERE draw_date BETWEEN ?1 AND ?2",
+    )?;
+    let stored: HashSet<String> = stmt
+        .query_map([format!("{}-01-01", year), format!("{}-12-31", year)], |row| row.get(0))?
+        .collect::<Result<_>>()?;
+
+    Ok(generate_lottery_dates(year)
+        .into_iter()
+        .map(|(date, month, year)| {
+            let draw_date = format_draw_date(&date, &month, &year);
+            let present = stored.contains(&draw_date);
+            (draw_date, present)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_draw() -> LotteryData {
+        let numbers = |list: &[&str]| Some(list.iter().map(|n| n.to_string()).collect::<Vec<_>>());
+        LotteryData {
+            draw_date: "2024-03-01".to_string(),
+            draw_no: "5".to_string(),
+            first_prize: Some("123456".to_string()),
+            last_two_digits: Some("56".to_string()),
+            last_three_digits: numbers(&["123", "456", "789", "012"]),
+            near_first: numbers(&["123455", "123457"]),
+            second_prize: numbers(&["111111", "222222"]),
+            third_prize: numbers(&["333333"]),
+            fourth_prize: None,
+            fifth_prize: numbers(&[]),
+        }
+    }
+
+    #[test]
+    fn saved_draw_loads_back_unchanged() {
+        let conn = create_in_memory_database().unwrap();
+        let saved = sample_draw();
+        save_lottery_result(&conn, &saved).unwrap();
+
+        let stored_last3: String = conn
+            .query_row("SELECT last_three_digits FROM lottery_results", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(stored_last3, "123,456,789,012");
+
+        let loaded = get_lottery_by_date(&conn, "2024-03-01").unwrap().unwrap();
+        assert_eq!(loaded.draw_date, saved.draw_date);
+        assert_eq!(loaded.draw_no, saved.draw_no);
+        assert_eq!(loaded.first_prize, saved.first_prize);
+        assert_eq!(loaded.last_two_digits, saved.last_two_digits);
+        assert_eq!(loaded.last_three_digits, saved.last_three_digits);
+        assert_eq!(loaded.near_first, saved.near_first);
+        assert_eq!(loaded.second_prize, saved.second_prize);
+        assert_eq!(loaded.third_prize, saved.third_prize);
+        assert_eq!(loaded.fourth_prize, None);
+        assert_eq!(loaded.fifth_prize, Some(Vec::new()));
+    }
+
+    #[test]
+    fn diff_with_a_missing_file_fails_without_creating_it() {
+        let conn = create_in_memory_database().unwrap();
+        let missing = std::env::temp_dir().join(format!("lotto-missing-{}.db", std::process::id()));
+
+        assert!(diff_databases(&conn, &missing.to_string_lossy()).is_err());
+        assert!(!missing.exists());
+    }
+
+    #[test]
+    fn search_with_a_missing_attachment_fails_without_creating_it() {
+        let conn = create_in_memory_database().unwrap();
+        let missing = std::env::temp_dir().join(format!("lotto-missing-attach-{}.db", std::process::id()));
+
+        assert!(attach_and_search(&conn, &[&missing.to_string_lossy()], "123456").is_err());
+        assert!(!missing.exists());
+    }
+}
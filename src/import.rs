@@ -0,0 +1,161 @@
+use crate::database::{get_lottery_by_date, import_lottery_result_with_raw};
+use crate::integrity::prize_count_issues;
+use crate::types::{LotteryData, LotteryResponse};
+use crate::utils::parse_draw_date;
+use rusqlite::Connection;
+use serde_json::{Value, json};
+use std::error::Error;
+use std::fs;
+use std::io;
+
+#[derive(Debug, Default)]
+pub struct ImportDirSummary {
+    pub succeeded: usize,
+    /// Files whose draw date was already stored.
+    pub skipped: usize,
+    /// `(filename, error)` for files that could not be read or parsed.
+    pub failed: Vec<(String, String)>,
+}
+
+/// Parses a saved GLO response, rejecting payloads without a usable draw.
+pub fn parse_lottery_json(content: &str) -> Result<LotteryData, String> {
+    let response: LotteryResponse = serde_json::from_str(content).map_err(|e| e.to_string())?;
+    let data = match response.data {
+        Some(data) if response.is_success() => data,
+        _ => return Err(format!("no lottery data (status: {})", response.status)),
+    };
+    parse_draw_date(&data.draw_date).map_err(|e| format!("invalid drawDate {}: {}", data.draw_date, e))?;
+    Ok(data)
+}
+
+/// A stored draw rebuilt into the response envelope `parse_lottery_json`
+/// reads, so it can be imported elsewhere like a freshly fetched draw. `None`
+/// if no draw is stored for the date.
+pub fn get_lottery_raw_format(conn: &Connection, draw_date: &str) -> rusqlite::Result<Option<Value>> {
+    Ok(get_lottery_by_date(conn, draw_date)?.map(|data| json!({ "status": "success", "data": data })))
+}
+
+/// Runs the import parse over every `*.json` file in `path` without touching
+/// the database. Returns `(filename, valid, error)` sorted by filename.
+pub fn validate_json_directory(path: &str) -> io::Result<Vec<(String, bool, Option<String>)>> {
+    let mut results = Vec::new();
+    for entry in fs::read_dir(path)? {
+        let path = entry?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+
+        let filename = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let outcome = fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| parse_lottery_json(&content));
+        match outcome {
+            Ok(_) => results.push((filename, true, None)),
+            Err(e) => results.push((filename, false, Some(e))),
+        }
+    }
+    results.sort();
+    Ok(results)
+}
+
+/// Imports every `*.json` file in `dir`. Files that fail to parse are recorded
+/// in the summary rather than aborting the import. With `strict`, so are files
+/// whose categories do not hold GLO's expected count of numbers.
+pub fn import_json_directory(conn: &Connection, dir: &str, strict: bool) -> Result<ImportDirSummary, Box<dyn Error>> {
+    let mut summary = ImportDirSummary::default();
+    let mut paths: Vec<_> = fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<io::Result<_>>()?;
+    paths.sort();
+
+    for path in paths {
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+
+        let filename = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) => {
+                summary.failed.push((filename, e.to_string()));
+                continue;
+            }
+        };
+        let parsed = parse_lottery_json(&content).and_then(|data| {
+            let issues = if strict { prize_count_issues(&data) } else { Vec::new() };
+            if issues.is_empty() { Ok(data) } else { Err(issues.join("; ")) }
+        });
+        match parsed {
+            Ok(data) => {
+                if import_lottery_result_with_raw(conn, &data, &content)?.was_new {
+                    summary.succeeded += 1;
+                } else {
+                    summary.skipped += 1;
+                }
+            }
+            Err(e) => summary.failed.push((filename, e)),
+        }
+    }
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::{create_in_memory_database, get_raw_json, save_lottery_result};
+
+    const RESPONSE: &str = r#"{"status": "success", "data": {"drawDate": "2024-03-01", "drawNo": "5", "first": "123456", "last2": "56"}}"#;
+
+    fn stored_rows(conn: &Connection) -> i64 {
+        conn.query_row("SELECT COUNT(*) FROM lottery_results", [], |row| row.get(0)).unwrap()
+    }
+
+    #[test]
+    fn importing_the_same_payload_twice_keeps_one_row() {
+        let conn = create_in_memory_database().unwrap();
+        let data = parse_lottery_json(RESPONSE).unwrap();
+
+        let first = import_lottery_result_with_raw(&conn, &data, RESPONSE).unwrap();
+        let second = import_lottery_result_with_raw(&conn, &data, RESPONSE).unwrap();
+
+        assert!(first.was_new);
+        assert!(!second.was_new);
+        assert_eq!(second.lottery_id, first.lottery_id);
+        assert_eq!(stored_rows(&conn), 1);
+    }
+
+    #[test]
+    fn reimporting_a_directory_skips_stored_draws() {
+        let conn = create_in_memory_database().unwrap();
+        let dir = std::env::temp_dir().join(format!("lotto-import-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("2024-03-01.json"), RESPONSE).unwrap();
+        let dir_path = dir.to_string_lossy();
+
+        let first = import_json_directory(&conn, &dir_path, false).unwrap();
+        let second = import_json_directory(&conn, &dir_path, false).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!((first.succeeded, first.skipped), (1, 0));
+        assert_eq!((second.succeeded, second.skipped), (0, 1));
+        assert_eq!(stored_rows(&conn), 1);
+    }
+
+    #[test]
+    fn reimporting_a_stored_date_only_fills_in_missing_raw_json() {
+        let conn = create_in_memory_database().unwrap();
+        let data = parse_lottery_json(RESPONSE).unwrap();
+        save_lottery_result(&conn, &data).unwrap();
+        let changed = RESPONSE.replace("\"last2\": \"56\"", "\"last2\": \"99\"");
+
+        let refill = import_lottery_result_with_raw(&conn, &data, RESPONSE).unwrap();
+        let conflicting = import_lottery_result_with_raw(&conn, &parse_lottery_json(&changed).unwrap(), &changed).unwrap();
+
+        assert!(!refill.was_new && !conflicting.was_new);
+        assert_eq!((refill.prize_numbers_inserted, conflicting.prize_numbers_inserted), (0, 0));
+        assert_eq!(stored_rows(&conn), 1);
+        assert_eq!(get_raw_json(&conn, "2024-03-01").unwrap().as_deref(), Some(RESPONSE));
+        let stored = get_lottery_by_date(&conn, "2024-03-01").unwrap().unwrap();
+        assert_eq!(stored.last_two_digits.as_deref(), Some("56"));
+    }
+}
@@ -0,0 +1,177 @@
+use crate::database::{get_all_lottery_results, get_lottery_by_date};
+use crate::types::{Category, LotteryData};
+use rusqlite::{Connection, Result};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+#[derive(Serialize, Debug)]
+pub struct Anomaly {
+    pub draw_date: String,
+    pub issue: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct IntegrityReport {
+    pub draws_checked: usize,
+    pub anomalies: Vec<Anomaly>,
+}
+
+/// Checks every stored draw for a first prize, the expected count of numbers
+/// in each category, numbers repeated within a category, numbers of the right
+/// length made only of digits, and dates stored more than once.
+pub fn verify_integrity(conn: &Connection) -> Result<IntegrityReport> {
+    let results = get_all_lottery_results(conn)?;
+    let mut anomalies = Vec::new();
+    let mut rows_per_date: HashMap<&str, usize> = HashMap::new();
+
+    for data in &results {
+        *rows_per_date.entry(&data.draw_date).or_default() += 1;
+
+        for issue in prize_count_issues(data) {
+            anomalies.push(Anomaly { draw_date: data.draw_date.clone(), issue });
+        }
+        for (category, numbers) in data.prize_categories() {
+            let mut seen = HashSet::new();
+            for number in numbers.iter().filter(|n| !seen.insert(n.as_str())) {
+                anomalies.push(Anomaly {
+                    draw_date: data.draw_date.clone(),
+                    issue: format!("{} lists {} more than once", category, number),
+                });
+            }
+            for number in numbers {
+                if !is_valid_number(category, &number) {
+                    anomalies.push(Anomaly {
+                        draw_date: data.draw_date.clone(),
+                        issue: format!("{} number {:?} is not {} digits", category, number, category.digits()),
+                    });
+                }
+            }
+        }
+    }
+
+    let mut duplicates: Vec<_> = rows_per_date.into_iter().filter(|(_, rows)| *rows > 1).collect();
+    duplicates.sort();
+    for (draw_date, rows) in duplicates {
+        anomalies.push(Anomaly { draw_date: draw_date.to_string(), issue: format!("stored {} times", rows) });
+    }
+
+    Ok(IntegrityReport { draws_checked: results.len(), anomalies })
+}
+
+/// One message per category whose number count differs from GLO's fixed
+/// count, a sign of a truncated or corrupt response. Used both by
+/// `verify_integrity` and by strict imports.
+pub fn prize_count_issues(data: &LotteryData) -> Vec<String> {
+    Category::ALL
+        .iter()
+        .filter_map(|&category| {
+            let count = data.numbers(category).len();
+            if count == category.expected_count() {
+                None
+            } else if category == Category::First && count == 0 {
+                Some("missing first prize".to_string())
+            } else {
+                Some(format!("{} has {} number(s), expected {}", category, count, category.expected_count()))
+            }
+        })
+        .collect()
+}
+
+/// The categories that have at least one number in the draw on `date`, in
+/// `Category::ALL` order, or `None` if no draw is stored. A category missing
+/// here usually means a partial import.
+pub fn get_draw_categories(conn: &Connection, date: &str) -> Result<Option<Vec<Category>>> {
+    Ok(get_lottery_by_date(conn, date)?.map(|data| {
+        data.prize_categories()
+            .into_iter()
+            .filter(|(_, numbers)| !numbers.is_empty())
+            .map(|(category, _)| category)
+            .collect()
+    }))
+}
+
+/// Whether `number` has the digit count GLO uses for `category`.
+pub fn is_valid_number(category: Category, number: &str) -> bool {
+    number.len() == category.digits() && number.chars().all(|c| c.is_ascii_digit())
+}
+
+#[derive(Serialize, Debug)]
+pub struct CrossDuplicate {
+    pub number: String,
+    pub categories: Vec<Category>,
+}
+
+/// Numbers listed under more than one category in the draw on `date`, with
+/// those categories, or `None` if no draw is stored. GLO never repeats a
+/// number across categories, so any hit usually means a bad import.
+pub fn find_cross_category_duplicates(conn: &Connection, date: &str) -> Result<Option<Vec<CrossDuplicate>>> {
+    let Some(data) = get_lottery_by_date(conn, date)? else {
+        return Ok(None);
+    };
+    let mut categories_by_number: BTreeMap<String, Vec<Category>> = BTreeMap::new();
+    for (category, numbers) in data.prize_categories() {
+        for number in numbers {
+            let categories = categories_by_number.entry(number).or_default();
+            if !categories.contains(&category) {
+                categories.push(category);
+            }
+        }
+    }
+    Ok(Some(
+        categories_by_number
+            .into_iter()
+            .filter(|(_, categories)| categories.len() > 1)
+            .map(|(number, categories)| CrossDuplicate { number, categories })
+            .collect(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::{create_in_memory_database, save_lottery_result};
+
+    /// A draw holding GLO's full count of distinct, well-formed numbers in every category.
+    fn complete_draw() -> LotteryData {
+        let numbers = |start: u32, count: u32| Some((start..start + count).map(|n| format!("{:06}", n)).collect());
+        LotteryData {
+            draw_date: "2024-03-01".to_string(),
+            draw_no: "5".to_string(),
+            first_prize: Some("123456".to_string()),
+            last_two_digits: Some("56".to_string()),
+            last_three_digits: Some(vec!["123".into(), "456".into(), "789".into(), "012".into()]),
+            near_first: Some(vec!["123455".into(), "123457".into()]),
+            second_prize: numbers(200000, 5),
+            third_prize: numbers(300000, 10),
+            fourth_prize: numbers(400000, 50),
+            fifth_prize: numbers(500000, 100),
+        }
+    }
+
+    #[test]
+    fn complete_draw_has_no_count_issues() {
+        assert!(prize_count_issues(&complete_draw()).is_empty());
+    }
+
+    #[test]
+    fn missing_first_prize_is_a_count_issue() {
+        let mut data = complete_draw();
+        data.first_prize = None;
+        assert_eq!(prize_count_issues(&data), ["missing first prize"]);
+    }
+
+    #[test]
+    fn verify_flags_a_number_repeated_within_a_category() {
+        let conn = create_in_memory_database().unwrap();
+        let mut data = complete_draw();
+        if let Some(second) = data.second_prize.as_mut() {
+            second[1] = second[0].clone();
+        }
+        save_lottery_result(&conn, &data).unwrap();
+
+        let report = verify_integrity(&conn).unwrap();
+
+        let issues: Vec<&str> = report.anomalies.iter().map(|a| a.issue.as_str()).collect();
+        assert_eq!(issues, ["second lists 200000 more than once"]);
+    }
+}
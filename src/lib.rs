@@ -0,0 +1,16 @@
+//! Shared fetching, storage and query logic for the lottery tools.
+
+pub mod analysis;
+pub mod api;
+pub mod config;
+pub mod database;
+pub mod import;
+pub mod integrity;
+pub mod maintenance;
+pub mod migrations;
+pub mod qr;
+pub mod report;
+pub mod source;
+pub mod tickets;
+pub mod types;
+pub mod utils;
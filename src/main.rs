@@ -1,147 +1,895 @@
-use reqwest;
-use rusqlite::{Connection, Result};
-use serde::{Deserialize, Serialize};
+use chrono::{FixedOffset, Local, SecondsFormat};
+use clap::{Parser, Subcommand};
+use lotto_core::analysis::{
+    compare_draws, cooccurrence, digit_position_frequency, draw_weekday_distribution,
+    last2_uniformity, number_due_score, overdue_numbers,
+};
+use lotto_core::api::{
+    FetchSummary, GLO_RESULT_URL, SingleFetchOutcome, fetch_and_save_range,
+    fetch_and_save_result_by_date, fetch_lottery_preview, fetch_lottery_result_by_date,
+};
+use lotto_core::config::{Config, DEFAULT_RESULTS_LIMIT};
+use lotto_core::database::{
+    attach_and_search, avg_prizes_per_draw, check_for_new_draw, create_database, days_since_number,
+    default_db_path, delete_range, diff_databases, draws_by_prize_count, first_prize_extremes,
+    first_prize_uniqueness, first_prizes_containing, get_created_at, get_database_stats,
+    get_latest_lottery_results, get_lottery_by_date, get_lottery_by_dates,
+    get_lottery_results_by_month, get_nth_latest, get_raw_json, is_near_first, neighbors,
+    number_lengths_by_category, number_timeline, open_read_only, search_number_page,
+    write_draws_wide_csv, year_coverage_map,
+};
+use lotto_core::integrity::{
+    find_cross_category_duplicates, get_draw_categories, is_valid_number, verify_integrity,
+};
+use lotto_core::maintenance::{backup_database, clear_all_data, export_sql_dump, optimize_database};
+use lotto_core::migrations::{apply_migrations, schema_version};
+use lotto_core::qr::generate_report_qr;
+use lotto_core::import::{get_lottery_raw_format, import_json_directory, validate_json_directory};
+use lotto_core::report::{
+    DEFAULT_REPORT_DIR, ReportOptions, ReportOutcome, audit_reports, export_year_bundle,
+    generate_and_save_stats_report, generate_report, generate_report_if_missing,
+    generate_reports_for_range, generate_stats_report, list_generated_files,
+};
+use lotto_core::source::{FileSource, GloSource, LotterySource};
+use lotto_core::tickets::check_tickets;
+use lotto_core::types::{Category, Language, MatchMode, SortOrder, prize_structure_template};
+use lotto_core::utils::DrawDate;
+use rusqlite::Connection;
+use serde::Serialize;
+use serde_json::json;
 use std::error::Error;
+use std::fs;
+use std::io::{self, BufWriter};
 
-#[derive(Serialize)]
-struct LotteryRequest {
-    date: String,
-    month: String,
-    year: String,
+#[derive(Parser)]
+#[command(name = "lottorust", about = "Fetch, store and query Thai government lottery results")]
+struct Cli {
+    /// SQLite database file (defaults to $LOTTERY_DB_PATH, then lottery.db)
+    #[arg(long, global = true)]
+    db_path: Option<String>,
+    /// Open the database read-only and refuse commands that write (or set $LOTTERY_READ_ONLY=true)
+    #[arg(long, global = true)]
+    read_only: bool,
+    #[command(subcommand)]
+    command: Command,
 }
 
-#[derive(Deserialize, Debug)]
-struct LotteryResponse {
-    status: String,
-    data: Option<LotteryData>,
+#[derive(Subcommand)]
+enum Command {
+    /// Import saved API responses from a directory of JSON files
+    Import {
+        dir: String,
+        /// Reject files whose categories do not hold GLO's expected count of numbers
+        #[arg(long)]
+        strict: bool,
+    },
+    /// Check which JSON files in a directory would import, without writing
+    Validate { dir: String },
+    /// Fetch every draw of a year (the 1st and 16th of each month)
+    Fetch {
+        year: String,
+        /// Requests in flight at once (defaults to $LOTTERY_FETCH_CONCURRENCY, then 2)
+        #[arg(long)]
+        concurrency: Option<usize>,
+        /// Read saved responses named YYYY-MM-DD.json from this directory instead of GLO
+        #[arg(long)]
+        from_dir: Option<String>,
+    },
+    /// Print the original GLO JSON stored for a draw
+    RawJson {
+        date: String,
+        /// Rebuild the JSON from the stored prizes instead, in a form `import` accepts
+        #[arg(long)]
+        rebuild: bool,
+    },
+    /// Check whether the latest scheduled draw is stored
+    CheckNewDraw {
+        /// If it is not, ask GLO whether results are out yet (nothing is saved)
+        #[arg(long)]
+        live: bool,
+    },
+    /// Fetch the scheduled draws between two dates (inclusive) that are not stored yet
+    FetchRange {
+        start_date: DrawDate,
+        end_date: DrawDate,
+        /// Requests in flight at once (defaults to $LOTTERY_FETCH_CONCURRENCY, then 2)
+        #[arg(long)]
+        concurrency: Option<usize>,
+        /// Read saved responses named YYYY-MM-DD.json from this directory instead of GLO
+        #[arg(long)]
+        from_dir: Option<String>,
+    },
+    /// Fetch the draw for one YYYY-MM-DD date and print it as JSON without saving it
+    Preview {
+        date: DrawDate,
+        /// Read saved responses named YYYY-MM-DD.json from this directory instead of GLO
+        #[arg(long)]
+        from_dir: Option<String>,
+    },
+    /// Fetch and save the draw for one YYYY-MM-DD date
+    FetchDate {
+        date: DrawDate,
+        /// Read saved responses named YYYY-MM-DD.json from this directory instead of GLO
+        #[arg(long)]
+        from_dir: Option<String>,
+        /// Report language: th or en
+        #[arg(long, default_value = "th")]
+        lang: Language,
+    },
+    /// Print the draws stored for several dates as a JSON array, in the order given
+    Lookup {
+        #[arg(required = true)]
+        dates: Vec<String>,
+    },
+    /// Show the stored results for a draw date
+    Report {
+        date: String,
+        /// Print the draw as JSON
+        #[arg(long)]
+        json: bool,
+        /// Write the report to a file instead and print its path
+        #[arg(long)]
+        save: bool,
+        #[arg(long, default_value = DEFAULT_REPORT_DIR)]
+        dir: String,
+        /// Gzip the saved report
+        #[arg(long)]
+        compress: bool,
+        /// With --save, regenerate an existing report, rewriting it if its content changed
+        #[arg(long)]
+        force: bool,
+        /// Report language: th or en
+        #[arg(long, default_value = "th")]
+        lang: Language,
+    },
+    /// Write a report file for every stored draw between two dates
+    ReportRange {
+        start_date: String,
+        end_date: String,
+        #[arg(long, default_value = DEFAULT_REPORT_DIR)]
+        dir: String,
+        /// Gzip the saved reports
+        #[arg(long)]
+        compress: bool,
+        /// Regenerate existing reports, rewriting those whose content changed
+        #[arg(long)]
+        force: bool,
+        /// Report language: th or en
+        #[arg(long, default_value = "th")]
+        lang: Language,
+    },
+    /// Print a summary report across all stored draws, or save it with --save
+    StatsReport {
+        #[arg(long)]
+        save: bool,
+        #[arg(long, default_value = DEFAULT_REPORT_DIR)]
+        dir: String,
+        /// Gzip the saved report
+        #[arg(long)]
+        compress: bool,
+    },
+    /// List report files without a stored draw and stored draws without a report
+    AuditReports {
+        #[arg(long, default_value = DEFAULT_REPORT_DIR)]
+        dir: String,
+    },
+    /// Zip the reports of every draw stored in a year, with an index
+    ExportYearBundle {
+        year: String,
+        #[arg(short, long)]
+        output: Option<String>,
+        /// Report language: th or en
+        #[arg(long, default_value = "th")]
+        lang: Language,
+    },
+    /// List the report files in a directory
+    ListReports {
+        #[arg(long, default_value = DEFAULT_REPORT_DIR)]
+        dir: String,
+    },
+    /// Show two draws side by side as JSON, flagging numbers they share
+    Compare { date_a: String, date_b: String },
+    /// List the draws that differ between this database and another file
+    Diff { other_path: String },
+    /// Show digit patterns within a draw as JSON, e.g. prizes ending in the last-2 prize
+    Cooccurrence { date: String },
+    /// Show the stored draws immediately before and after a date
+    Neighbors { date: String },
+    /// Show which scheduled draw dates of a year are stored
+    Coverage { year: String },
+    /// Check six-digit tickets against a draw
+    CheckTickets {
+        date: String,
+        #[arg(required = true)]
+        tickets: Vec<String>,
+    },
+    /// Check whether a number is adjacent to a draw's first prize
+    NearFirst { date: String, number: String },
+    /// List, per category and oldest first, the draws in which a number won
+    Timeline {
+        number: String,
+        /// exact, or suffix to match prize numbers ending in the number
+        #[arg(long = "match", default_value = "exact")]
+        mode: MatchMode,
+    },
+    /// Show every prize of the most recent stored draw
+    LatestDraw {
+        /// Print the draw as JSON
+        #[arg(long)]
+        json: bool,
+        /// Show the nth most recent draw instead; 2 is the draw before last
+        #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+        nth: u32,
+        /// Report language: th or en
+        #[arg(long, default_value = "th")]
+        lang: Language,
+    },
+    /// List the most recent stored draws
+    Latest {
+        /// How many draws to list; capped at LOTTERY_MAX_RESULTS
+        #[arg(long, default_value_t = DEFAULT_RESULTS_LIMIT as i32, allow_negative_numbers = true)]
+        limit: i32,
+    },
+    /// List the draws stored for a month
+    Month {
+        year: i32,
+        #[arg(value_parser = clap::value_parser!(u32).range(1..=12))]
+        month: u32,
+        /// asc for oldest first
+        #[arg(long, default_value = "desc")]
+        order: SortOrder,
+    },
+    /// List the draws and categories in which a number won
+    Search {
+        number: String,
+        /// Also search another lottery database (repeatable)
+        #[arg(long = "attach")]
+        attach: Vec<String>,
+        /// Show at most this many matches
+        #[arg(long, conflicts_with = "attach")]
+        limit: Option<usize>,
+        /// Skip this many matches first (with --limit)
+        #[arg(long, default_value_t = 0, requires = "limit")]
+        offset: usize,
+    },
+    /// List first prizes containing a digit sequence, e.g. 888
+    FirstContaining { substring: String },
+    /// Compare the number of distinct first prizes with the number of draws
+    FirstUniqueness,
+    /// Show the lowest and highest first prizes on record
+    FirstExtremes,
+    /// List draws by how many prize numbers they contain
+    DrawsByPrizeCount {
+        /// asc to surface sparse (likely incomplete) draws first
+        #[arg(long, default_value = "asc")]
+        order: SortOrder,
+        #[arg(long, default_value_t = 10)]
+        limit: i32,
+    },
+    /// Show the average number of prize numbers per draw
+    AvgPrizes,
+    /// Count each last-2 ending and compare with a uniform spread (descriptive only)
+    Last2Uniformity,
+    /// Count how often each digit appears at each position of a category's numbers
+    DigitFrequency {
+        #[arg(long, default_value = "first")]
+        category: Category,
+    },
+    /// List the last-2 or last-3 endings that have gone longest without winning
+    Overdue {
+        #[arg(long, default_value_t = 2, value_parser = clap::value_parser!(u32).range(2..=3))]
+        digits: u32,
+        #[arg(long, default_value_t = 10)]
+        limit: usize,
+    },
+    /// Count the stored draws that fell on each weekday
+    Weekdays,
+    /// Compare a number's win intervals in a category with chance, as JSON
+    DueScore {
+        number: String,
+        #[arg(long, default_value = "last2")]
+        category: Category,
+    },
+    /// Show how many draws are stored and the dates they span
+    Stats,
+    /// Print the resolved settings: database path, report directory, API URL and fetch limits
+    Config,
+    /// List the distinct number lengths seen in each prize category
+    NumberLengths,
+    /// Show how many days ago a number last won a prize
+    DaysSince { number: String },
+    /// Export draws between two dates as CSV, one row per draw
+    ExportCsv {
+        start_date: String,
+        end_date: String,
+        /// Output file (defaults to stdout)
+        #[arg(long, short)]
+        output: Option<String>,
+    },
+    /// Print the expected number count, digit length and display name of each category
+    PrizeStructure,
+    /// Delete every draw between two dates (inclusive)
+    DeleteRange {
+        start_date: String,
+        end_date: String,
+        /// Required, as the deletion cannot be undone
+        #[arg(long)]
+        confirm: bool,
+        /// Back the database up to this file before deleting
+        #[arg(long)]
+        backup: Option<String>,
+    },
+    /// Delete every stored draw and drawless date, keeping the schema
+    ClearAll {
+        /// Required, as the deletion cannot be undone
+        #[arg(long)]
+        confirm: bool,
+        /// Back the database up to this file before deleting
+        #[arg(long)]
+        backup: Option<String>,
+    },
+    /// Write the schema and data as SQL statements to a file
+    ExportSql { path: String },
+    /// Copy the database to a file, safe even while it is being written
+    Backup { dest_path: String },
+    /// Reclaim unused space and refresh query planner statistics
+    Optimize,
+    /// Check stored draws for missing, malformed or duplicated data
+    Verify,
+    /// List numbers that appear in more than one prize category of a draw
+    CrossDuplicates { date: String },
+    /// Show when a draw was stored, in UTC and Thai time
+    StoredAt { date: String },
+    /// List which prize categories have numbers in a stored draw
+    Categories { date: String },
+    /// Apply pending schema migrations and report how many ran
+    Migrate,
+    /// Write a PNG QR code linking to a draw's report under BASE_URL
+    Qr {
+        date: String,
+        base_url: String,
+        /// Output file (defaults to lottery_report_<date>.png)
+        #[arg(long, short)]
+        output: Option<String>,
+    },
 }
 
-#[derive(Deserialize, Debug)]
-struct LotteryData {
-    #[serde(rename = "drawDate")]
-    draw_date: String,
-    #[serde(rename = "drawNo")]
-    draw_no: String,
-    #[serde(rename = "first")]
-    first_prize: Option<String>,
-    #[serde(rename = "last2")]
-    last_two_digits: Option<String>,
-    #[serde(rename = "last3")]
-    last_three_digits: Option<Vec<String>>,
-    #[serde(rename = "near1")]
-    near_first: Option<Vec<String>>,
-    #[serde(rename = "second")]
-    second_prize: Option<Vec<String>>,
-    #[serde(rename = "third")]
-    third_prize: Option<Vec<String>>,
-    #[serde(rename = "fourth")]
-    fourth_prize: Option<Vec<String>>,
-    #[serde(rename = "fifth")]
-    fifth_prize: Option<Vec<String>>,
+impl Command {
+    fn writes_database(&self) -> bool {
+        matches!(
+            self,
+            Command::Import { .. }
+                | Command::Fetch { .. }
+                | Command::FetchRange { .. }
+                | Command::FetchDate { .. }
+                | Command::DeleteRange { .. }
+                | Command::ClearAll { .. }
+                | Command::Optimize
+                | Command::Migrate
+        )
+    }
 }
 
-fn create_database() -> Result<Connection> {
-    let conn = Connection::open("lottery.db")?;
-    
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS lottery_results (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            draw_date TEXT NOT NULL,
-            draw_no TEXT NOT NULL,
-            first_prize TEXT,
-            last_two_digits TEXT,
-            last_three_digits TEXT,
-            near_first TEXT,
-            second_prize TEXT,
-            third_prize TEXT,
-            fourth_prize TEXT,
-            fifth_prize TEXT,
-            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
-        )",
-        [],
-    )?;
-    
-    Ok(conn)
+/// Tells the user when a list was cut to `LOTTERY_MAX_RESULTS` entries.
+fn print_truncation_note(shown: usize, total: usize) {
+    if total > shown {
+        println!("Showing {} of {} (truncated at LOTTERY_MAX_RESULTS)", shown, total);
+    }
 }
 
-fn save_lottery_result(conn: &Connection, data: &LotteryData) -> Result<()> {
-    conn.execute(
-        "INSERT INTO lottery_results (
-            draw_date, draw_no, first_prize, last_two_digits, last_three_digits,
-            near_first, second_prize, third_prize, fourth_prize, fifth_prize
-        ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
-        (
-            &data.draw_date,
-            &data.draw_no,
-            &data.first_prize,
-            &data.last_two_digits,
-            &data.last_three_digits.as_ref().map(|v| v.join(",")),
-            &data.near_first.as_ref().map(|v| v.join(",")),
-            &data.second_prize.as_ref().map(|v| v.join(",")),
-            &data.third_prize.as_ref().map(|v| v.join(",")),
-            &data.fourth_prize.as_ref().map(|v| v.join(",")),
-            &data.fifth_prize.as_ref().map(|v| v.join(",")),
-        ),
-    )?;
+/// JSON output for single-item lookups always has the same shape, so a missing
+/// item reads as `{"found": false, "result": null}` rather than a bare null.
+fn print_found_json<T: Serialize>(result: Option<T>) -> Result<(), Box<dyn Error>> {
+    let output = json!({ "found": result.is_some(), "result": result });
+    println!("{}", serde_json::to_string_pretty(&output)?);
     Ok(())
 }
 
-async fn fetch_lottery_result(date: &str, month: &str, year: &str) -> Result<LotteryResponse, Box<dyn Error>> {
-    let client = reqwest::Client::new();
-    let request_body = LotteryRequest {
-        date: date.to_string(),
-        month: month.to_string(),
-        year: year.to_string(),
-    };
-    
-    let response = client
-        .post("https://www.glo.or.th/api/checking/getLotteryResult")
-        .header("Content-Type", "application/json")
-        .json(&request_body)
-        .send()
-        .await?;
-    
-    let lottery_response: LotteryResponse = response.json().await?;
-    Ok(lottery_response)
+fn lottery_source(from_dir: Option<String>, config: &Config) -> Box<dyn LotterySource> {
+    match from_dir {
+        Some(dir) => Box::new(FileSource { dir: dir.into() }),
+        None => Box::new(GloSource::new(config)),
+    }
+}
+
+async fn fetch_year(
+    conn: &Connection,
+    source: &dyn LotterySource,
+    year: &str,
+    config: &Config,
+) -> Result<(), Box<dyn Error>> {
+    println!(
+        "Fetching lottery results for {} ({} request(s) at a time)",
+        year, config.fetch_concurrency
+    );
+    let (start_date, end_date) = (format!("{}-01-01", year), format!("{}-12-31", year));
+    let today = Local::now().date_naive();
+    let summary = fetch_and_save_range(conn, source, &start_date, &end_date, config, today).await?;
+    print_fetch_summary(&summary);
+    Ok(())
+}
+
+fn print_fetch_summary(summary: &FetchSummary) {
+    for draw_date in &summary.saved {
+        println!("Results for {} saved to database successfully!", draw_date);
+    }
+    for draw_date in &summary.no_draw {
+        println!("No draw on {}", draw_date);
+    }
+    for draw_date in &summary.not_yet_drawn {
+        println!("Results for {} are not out yet", draw_date);
+    }
+    for (draw_date, error) in &summary.failed {
+        eprintln!("Error fetching lottery results for {}: {}", draw_date, error);
+    }
+    println!(
+        "{} saved, {} already stored, {} without a draw, {} not out yet, {} skipped as known drawless, {} failed",
+        summary.saved.len(),
+        summary.already_stored.len(),
+        summary.no_draw.len(),
+        summary.not_yet_drawn.len(),
+        summary.skipped.len(),
+        summary.failed.len()
+    );
+    if !summary.failed.is_empty() {
+        eprintln!("Re-run to retry the failed dates");
+    }
 }
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
-    let conn = create_database()?;
-    
-    let date = "01";
-    let month = "03";
-    let year = "2024";
-    
-    println!("Fetching lottery results for {}/{}/{}", date, month, year);
-    
-    match fetch_lottery_result(date, month, year).await {
-        Ok(response) => {
-            if response.status == "success" {
-                if let Some(data) = response.data {
-                    println!("Lottery results fetched successfully!");
-                    println!("Draw Date: {}", data.draw_date);
-                    println!("Draw No: {}", data.draw_no);
-                    if let Some(first) = &data.first_prize {
-                        println!("First Prize: {}", first);
-                    }
-                    if let Some(last2) = &data.last_two_digits {
-                        println!("Last Two Digits: {}", last2);
-                    }
-                    
-                    save_lottery_result(&conn, &data)?;
-                    println!("Results saved to database successfully!");
+    let cli = Cli::parse();
+    let db_path = cli.db_path.unwrap_or_else(default_db_path);
+    let read_only = cli.read_only || Config::load().read_only;
+    if read_only && cli.command.writes_database() {
+        return Err("database is read-only".into());
+    }
+    // `migrate` opens the file as-is so it can report what it applied itself.
+    let conn = match cli.command {
+        _ if read_only => open_read_only(&db_path)?,
+        Command::Migrate => Connection::open(&db_path)?,
+        _ => create_database(&db_path)?,
+    };
+
+    match cli.command {
+        Command::Import { dir, strict } => {
+            let summary = import_json_directory(&conn, &dir, strict)?;
+            for (filename, error) in &summary.failed {
+                eprintln!("Failed {}: {}", filename, error);
+            }
+            println!(
+                "{} imported, {} already stored, {} failed",
+                summary.succeeded,
+                summary.skipped,
+                summary.failed.len()
+            );
+        }
+        Command::Validate { dir } => {
+            for (filename, valid, error) in validate_json_directory(&dir)? {
+                if valid {
+                    println!("{}: ok", filename);
                 } else {
-                    println!("No lottery data found for the specified date.");
+                    println!("{}: invalid ({})", filename, error.unwrap_or_default());
+                }
+            }
+        }
+        Command::Fetch { year, concurrency, from_dir } => {
+            let mut config = Config::load();
+            if let Some(concurrency) = concurrency {
+                config.fetch_concurrency = concurrency.max(1);
+            }
+            let source = lottery_source(from_dir, &config);
+            fetch_year(&conn, source.as_ref(), &year, &config).await?
+        }
+        Command::RawJson { date, rebuild: true } => match get_lottery_raw_format(&conn, &date)? {
+            Some(envelope) => println!("{}", serde_json::to_string_pretty(&envelope)?),
+            None => println!("No lottery results stored for {}", date),
+        },
+        Command::RawJson { date, .. } => match get_raw_json(&conn, &date)? {
+            Some(raw_json) => println!("{}", raw_json),
+            None => println!("No raw JSON stored for {}", date),
+        },
+        Command::CheckNewDraw { live } => {
+            let (draw_date, stored) = check_for_new_draw(&conn, Local::now().date_naive())?;
+            if stored {
+                println!("Results for {} are already stored", draw_date);
+            } else if live {
+                let response = fetch_lottery_result_by_date(&draw_date, Config::load().request_timeout).await?;
+                if response.is_success() && response.data.is_some() {
+                    println!("Results for {} are out but not stored; run fetch-date {}", draw_date, draw_date);
+                } else {
+                    println!("Results for {} are not out yet", draw_date);
                 }
             } else {
-                println!("API returned error status: {}", response.status);
+                println!("Results for {} are not stored", draw_date);
+            }
+        }
+        Command::FetchRange { start_date, end_date, concurrency, from_dir } => {
+            let mut config = Config::load();
+            if let Some(concurrency) = concurrency {
+                config.fetch_concurrency = concurrency.max(1);
+            }
+            let source = lottery_source(from_dir, &config);
+            let (start_date, end_date) = (start_date.to_string(), end_date.to_string());
+            let summary = fetch_and_save_range(&conn, source.as_ref(), &start_date, &end_date, &config, Local::now().date_naive())
+                .await?;
+            print_fetch_summary(&summary);
+        }
+        Command::Preview { date, from_dir } => {
+            let source = lottery_source(from_dir, &Config::load());
+            print_found_json(fetch_lottery_preview(source.as_ref(), &date.to_string()).await?)?;
+        }
+        Command::FetchDate { date, lang, from_dir } => {
+            let source = lottery_source(from_dir, &Config::load());
+            let today = Local::now().date_naive();
+            match fetch_and_save_result_by_date(&conn, source.as_ref(), &date.to_string(), today).await? {
+                SingleFetchOutcome::Saved(data) => print!("{}", generate_report(&data, lang)),
+                SingleFetchOutcome::AlreadyStored => println!("Results for {} are already stored", date),
+                SingleFetchOutcome::NoDraw => println!("No draw on {}", date),
+                SingleFetchOutcome::NotYetDrawn => println!("Results for {} are not out yet", date),
+            }
+        }
+        Command::Lookup { dates } => {
+            let results = get_lottery_by_dates(&conn, &dates)?;
+            let output: Vec<_> = dates
+                .iter()
+                .zip(results)
+                .map(|(date, result)| json!({ "date": date, "found": result.is_some(), "result": result }))
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&output)?);
+        }
+        Command::Report { date, save: true, dir, compress, force, lang, .. } => {
+            match generate_report_if_missing(&conn, &date, &ReportOptions { dir, compress, force, lang })? {
+                ReportOutcome::Written(path) => println!("Report written to {}", path.display()),
+                ReportOutcome::Unchanged(path) => println!("Report at {} is already up to date", path.display()),
+                ReportOutcome::Skipped(path) => {
+                    println!("Report already exists at {} (use --force to regenerate)", path.display())
+                }
+                ReportOutcome::NoDraw => println!("No lottery results stored for {}", date),
+            }
+        }
+        Command::Report { date, json: true, .. } => print_found_json(get_lottery_by_date(&conn, &date)?)?,
+        Command::Report { date, lang, .. } => match get_lottery_by_date(&conn, &date)? {
+            Some(data) => print!("{}", generate_report(&data, lang)),
+            None => println!("No lottery results stored for {}", date),
+        },
+        Command::StatsReport { save: true, dir, compress } => {
+            let options = ReportOptions { dir, compress, ..Default::default() };
+            println!("Report written to {}", generate_and_save_stats_report(&conn, &options)?.display());
+        }
+        Command::StatsReport { .. } => print!("{}", generate_stats_report(&conn)?),
+        Command::AuditReports { dir } => {
+            let audit = audit_reports(&conn, &dir)?;
+            for filename in &audit.stale {
+                println!("stale: {}", filename);
+            }
+            for draw_date in &audit.missing {
+                println!("missing: {}", draw_date);
+            }
+            println!("{} stale report(s), {} draw(s) without a report", audit.stale.len(), audit.missing.len());
+        }
+        Command::ExportYearBundle { year, output, lang } => {
+            let dest = output.unwrap_or_else(|| format!("lottery_reports_{}.zip", year));
+            let count = export_year_bundle(&conn, &year, &dest, lang)?;
+            println!("Bundled {} reports into {}", count, dest);
+        }
+        Command::ListReports { dir } => {
+            for filename in list_generated_files(&dir)? {
+                println!("{}", filename);
+            }
+        }
+        Command::ReportRange { start_date, end_date, dir, compress, force, lang } => {
+            let options = ReportOptions { dir, compress, force, lang };
+            let (mut written, mut unchanged, mut skipped) = (0, 0, 0);
+            for outcome in generate_reports_for_range(&conn, &start_date, &end_date, &options)? {
+                match outcome {
+                    ReportOutcome::Written(path) => {
+                        written += 1;
+                        println!("Wrote {}", path.display());
+                    }
+                    ReportOutcome::Unchanged(_) => unchanged += 1,
+                    ReportOutcome::Skipped(_) => skipped += 1,
+                    ReportOutcome::NoDraw => {}
+                }
+            }
+            println!(
+                "{} report(s) written to {}, {} unchanged, {} already existed",
+                written, options.dir, unchanged, skipped
+            );
+        }
+        Command::Compare { date_a, date_b } => {
+            println!("{}", serde_json::to_string_pretty(&compare_draws(&conn, &date_a, &date_b)?)?);
+        }
+        Command::Cooccurrence { date } => print_found_json(cooccurrence(&conn, &date)?)?,
+        Command::Diff { other_path } => {
+            let diff = diff_databases(&conn, &other_path)?;
+            for draw_date in &diff.only_in_primary {
+                println!("only in {}: {}", db_path, draw_date);
+            }
+            for draw_date in &diff.only_in_other {
+                println!("only in {}: {}", other_path, draw_date);
             }
+            for draw_date in &diff.differing {
+                println!("differs: {}", draw_date);
+            }
+            println!(
+                "{} only here, {} only there, {} differing",
+                diff.only_in_primary.len(),
+                diff.only_in_other.len(),
+                diff.differing.len()
+            );
         }
-        Err(e) => {
-            eprintln!("Error fetching lottery results: {}", e);
+        Command::Neighbors { date } => {
+            let (previous, next) = neighbors(&conn, &date)?;
+            println!("Previous: {}", previous.as_deref().unwrap_or("none"));
+            println!("Next: {}", next.as_deref().unwrap_or("none"));
+        }
+        Command::Coverage { year } => {
+            let coverage = year_coverage_map(&conn, &year)?;
+            for (draw_date, present) in &coverage {
+                println!("{} {}", draw_date, if *present { "stored" } else { "missing" });
+            }
+            let stored = coverage.iter().filter(|(_, present)| *present).count();
+            println!("{} of {} scheduled draws stored", stored, coverage.len());
+        }
+        Command::CheckTickets { date, tickets } => match check_tickets(&conn, &date, &tickets)? {
+            Some(checks) => {
+                for check in checks {
+                    if !is_valid_number(Category::First, &check.ticket) {
+                        println!("{}: not a six-digit ticket", check.ticket);
+                    } else if check.matches.is_empty() {
+                        println!("{}: no prize", check.ticket);
+                    }
+                    for prize in check.matches {
+                        println!("{}: {} ({})", check.ticket, prize.category.display_name_en(), prize.number);
+                    }
+                }
+            }
+            None => println!("No lottery results stored for {}", date),
+        },
+        Command::NearFirst { date, number } => match is_near_first(&conn, &date, &number)? {
+            Some(true) => println!("{} is next to the first prize of {}", number, date),
+            Some(false) => println!("{} is not next to the first prize of {}", number, date),
+            None => println!("No first prize stored for {}", date),
+        },
+        Command::Timeline { number, mode } => {
+            let timeline = number_timeline(&conn, &number, mode)?;
+            if timeline.is_empty() {
+                println!("{} has not won any prize", number);
+            }
+            for (category, dates) in timeline {
+                println!("{} ({} time(s)): {}", category, dates.len(), dates.join(", "));
+            }
+        }
+        Command::LatestDraw { json: true, nth, .. } => print_found_json(get_nth_latest(&conn, nth as usize)?)?,
+        Command::LatestDraw { lang, nth, .. } => match get_nth_latest(&conn, nth as usize)? {
+            Some(data) => print!("{}", generate_report(&data, lang)),
+            None if nth == 1 => println!("No lottery results stored"),
+            None => println!("Fewer than {} draws stored", nth),
+        },
+        Command::Latest { limit } => {
+            let config = Config::load();
+            let (limit, capped) = config.clamp_limit(limit);
+            if capped {
+                eprintln!("Limit capped at {} (LOTTERY_MAX_RESULTS)", limit);
+            }
+            for data in get_latest_lottery_results(&conn, limit)? {
+                println!("{} (draw {}): {}", data.draw_date, data.draw_no, data.first_prize.unwrap_or_default());
+            }
+        }
+        Command::Month { year, month, order } => {
+            for data in get_lottery_results_by_month(&conn, year, month, order)? {
+                println!("{} (draw {}): {}", data.draw_date, data.draw_no, data.first_prize.unwrap_or_default());
+            }
+        }
+        Command::Search { number, attach, .. } if !attach.is_empty() => {
+            let paths: Vec<&str> = attach.iter().map(String::as_str).collect();
+            let matches = attach_and_search(&conn, &paths, &number)?;
+            if matches.is_empty() {
+                println!("{} has not won any prize", number);
+            }
+            let total = matches.len();
+            let max_results = Config::load().max_results;
+            for (source, draw_date, category) in matches.into_iter().take(max_results) {
+                println!("{}: {}: {}", source, draw_date, category);
+            }
+            print_truncation_note(total.min(max_results), total);
+        }
+        Command::Search { number, limit: Some(limit), offset, .. } => {
+            let limit = limit.min(Config::load().max_results);
+            let page = search_number_page(&conn, &number, limit, offset)?;
+            for (draw_date, category) in &page.matches {
+                println!("{}: {}", draw_date, category);
+            }
+            println!("Showing {} of {} match(es) from offset {}", page.matches.len(), page.total, offset);
+        }
+        Command::Search { number, .. } => {
+            let page = search_number_page(&conn, &number, Config::load().max_results, 0)?;
+            if page.total == 0 {
+                println!("{} has not won any prize", number);
+            }
+            for (draw_date, category) in &page.matches {
+                println!("{}: {}", draw_date, category);
+            }
+            print_truncation_note(page.matches.len(), page.total);
+        }
+        Command::FirstExtremes => match first_prize_extremes(&conn)? {
+            Some([(lowest_date, lowest), (highest_date, highest)]) => {
+                println!("Lowest:  {} ({})", lowest, lowest_date);
+                println!("Highest: {} ({})", highest, highest_date);
+            }
+            None => println!("No first prizes stored"),
+        },
+        Command::FirstContaining { substring } => {
+            let matches = first_prizes_containing(&conn, &substring)?;
+            let total = matches.len();
+            let max_results = Config::load().max_results;
+            for (draw_date, first_prize) in matches.into_iter().take(max_results) {
+                println!("{}: {}", draw_date, first_prize);
+            }
+            print_truncation_note(total.min(max_results), total);
+        }
+        Command::FirstUniqueness => {
+            let (distinct, total) = first_prize_uniqueness(&conn)?;
+            println!("{} distinct first prizes across {} draws", distinct, total);
+        }
+        Command::DrawsByPrizeCount { order, limit } => {
+            for (draw_date, count) in draws_by_prize_count(&conn, order, limit)? {
+                println!("{}: {} numbers", draw_date, count);
+            }
+        }
+        Command::AvgPrizes => {
+            println!("{:.2} prize numbers per draw", avg_prizes_per_draw(&conn)?);
+        }
+        Command::Last2Uniformity => {
+            println!("{}", serde_json::to_string_pretty(&last2_uniformity(&conn)?)?);
+        }
+        Command::DigitFrequency { category } => {
+            println!("pos  {}", (0..10).map(|d| format!("{:>5}", d)).collect::<String>());
+            for (position, counts) in digit_position_frequency(&conn, category)?.iter().enumerate() {
+                println!("{:>3}  {}", position + 1, counts.iter().map(|c| format!("{:>5}", c)).collect::<String>());
+            }
+        }
+        Command::Overdue { digits, limit } => {
+            for (ending, last_date, draws_ago) in overdue_numbers(&conn, digits)?.into_iter().take(limit) {
+                match last_date {
+                    Some(last_date) => println!("{}: last won {} ({} draws ago)", ending, last_date, draws_ago),
+                    None => println!("{}: never won", ending),
+                }
+            }
+        }
+        Command::Weekdays => {
+            let weekdays = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+            for (weekday, count) in weekdays.iter().zip(draw_weekday_distribution(&conn)?) {
+                println!("{}: {}", weekday, count);
+            }
+        }
+        Command::DueScore { number, category } => {
+            println!("{}", serde_json::to_string_pretty(&number_due_score(&conn, &number, category)?)?);
+        }
+        Command::Config => {
+            let config = Config::load();
+            let settings = json!({
+                "db_path": db_path,
+                "report_dir": DEFAULT_REPORT_DIR,
+                "api_url": GLO_RESULT_URL,
+                "fetch_concurrency": config.fetch_concurrency,
+                "request_delay_ms": config.request_delay.as_millis() as u64,
+                "request_timeout_secs": config.request_timeout.as_secs(),
+                "max_results": config.max_results,
+                "read_only": read_only,
+            });
+            println!("{}", serde_json::to_string_pretty(&settings)?);
+        }
+        Command::Stats => {
+            let stats = get_database_stats(&conn)?;
+            println!("Total draws: {}", stats.total_draws);
+            if let (Some(first), Some(last)) = (stats.first_draw, stats.last_draw) {
+                println!("Date range: {} to {}", first, last);
+            }
+        }
+        Command::NumberLengths => {
+            for (category, lengths) in number_lengths_by_category(&conn)? {
+                let lengths: Vec<String> = lengths.iter().map(|l| l.to_string()).collect();
+                println!("{}: {}", category, lengths.join(", "));
+            }
+        }
+        Command::DaysSince { number } => match days_since_number(&conn, &number, Local::now().date_naive())? {
+            Some(days) => println!("{} last won {} days ago", number, days),
+            None => println!("{} has not won any prize", number),
+        },
+        Command::ExportCsv { start_date, end_date, output } => {
+            match output {
+                Some(output) => {
+                    let mut file = BufWriter::new(fs::File::create(&output)?);
+                    write_draws_wide_csv(&conn, &start_date, &end_date, &mut file)?;
+                    println!("CSV written to {}", output);
+                }
+                None => write_draws_wide_csv(&conn, &start_date, &end_date, &mut io::stdout().lock())?,
+            }
+        }
+        Command::PrizeStructure => {
+            println!("{}", serde_json::to_string_pretty(&prize_structure_template())?);
+        }
+        Command::DeleteRange { start_date, end_date, confirm, backup } => {
+            if !confirm {
+                return Err("Refusing to delete without --confirm".into());
+            }
+            if let Some(backup) = backup {
+                backup_database(&conn, &backup)?;
+                println!("Database backed up to {}", backup);
+            }
+            let deleted = delete_range(&conn, &start_date, &end_date)?;
+            println!("Deleted {} draw(s) between {} and {}", deleted, start_date, end_date);
+        }
+        Command::ClearAll { confirm, backup } => {
+            if !confirm {
+                return Err("Refusing to clear the database without --confirm".into());
+            }
+            if let Some(backup) = backup {
+                backup_database(&conn, &backup)?;
+                println!("Database backed up to {}", backup);
+            }
+            println!("Deleted {} draw(s)", clear_all_data(&conn, confirm)?);
+        }
+        Command::ExportSql { path } => {
+            fs::write(&path, export_sql_dump(&conn)?)?;
+            println!("SQL dump written to {}", path);
+        }
+        Command::Backup { dest_path } => {
+            backup_database(&conn, &dest_path)?;
+            println!("Database backed up to {}", dest_path);
+        }
+        Command::Optimize => {
+            let (before, after) = optimize_database(&conn)?;
+            println!("Database size: {} bytes -> {} bytes", before, after);
+        }
+        Command::Verify => {
+            let report = verify_integrity(&conn)?;
+            for anomaly in &report.anomalies {
+                println!("{}: {}", anomaly.draw_date, anomaly.issue);
+            }
+            println!("{} draw(s) checked, {} anomalies", report.draws_checked, report.anomalies.len());
+        }
+        Command::CrossDuplicates { date } => match find_cross_category_duplicates(&conn, &date)? {
+            Some(duplicates) => {
+                for duplicate in &duplicates {
+                    let names: Vec<&str> = duplicate.categories.iter().map(|c| c.as_str()).collect();
+                    println!("{}: {}", duplicate.number, names.join(", "));
+                }
+                println!("{} number(s) in more than one category", duplicates.len());
+            }
+            None => println!("No lottery results stored for {}", date),
+        },
+        Command::StoredAt { date } => match get_created_at(&conn, &date)? {
+            Some(created_at) => {
+                let bangkok = FixedOffset::east_opt(7 * 3600).expect("UTC+7 is a valid offset");
+                println!("{} stored at {}", date, created_at.to_rfc3339_opts(SecondsFormat::Secs, true));
+                println!("Thai time: {}", created_at.with_timezone(&bangkok).to_rfc3339_opts(SecondsFormat::Secs, false));
+            }
+            None => println!("No lottery results stored for {}", date),
+        },
+        Command::Categories { date } => match get_draw_categories(&conn, &date)? {
+            Some(present) => {
+                for category in Category::ALL {
+                    let mark = if present.contains(&category) { "present" } else { "missing" };
+                    println!("{}: {}", category, mark);
+                }
+                println!("{} of {} categories present", present.len(), Category::ALL.len());
+            }
+            None => println!("No lottery results stored for {}", date),
+        },
+        Command::Migrate => {
+            let applied = apply_migrations(&conn)?;
+            println!("Applied {} migration(s); schema is at version {}", applied, schema_version(&conn)?);
+        }
+        Command::Qr { date, base_url, output } => {
+            let png = generate_report_qr(&date, &base_url)?;
+            let output = output.unwrap_or_else(|| format!("lottery_report_{}.png", date));
+            fs::write(&output, png)?;
+            println!("QR code written to {}", output);
         }
     }
-    
+
     Ok(())
-}
\ No newline at end of file
+}
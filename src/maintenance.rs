@@ -0,0 +1,93 @@
+use rusqlite::backup::Backup;
+use rusqlite::types::ValueRef;
+use rusqlite::{Connection, Result};
+use std::time::Duration;
+
+fn sql_literal(value: ValueRef) -> String {
+    match value {
+        ValueRef::Null => "NULL".to_string(),
+        ValueRef::Integer(i) => i.to_string(),
+        ValueRef::Real(f) => f.to_string(),
+        ValueRef::Text(t) => format!("'{}'", String::from_utf8_lossy(t).replace('\'', "''")),
+        ValueRef::Blob(b) => format!("X'{}'", b.iter().map(|byte| format!("{:02X}", byte)).collect::<String>()),
+    }
+}
+
+/// The whole database as `CREATE` and `INSERT` statements that rebuild it
+/// when run against an empty SQLite file.
+pub fn export_sql_dump(conn: &Connection) -> Result<String> {
+    let mut dump = String::from("BEGIN TRANSACTION;\n");
+
+    let mut stmt = conn.prepare(
+        "SELECT name, sql FROM sqlite_master
+         WHERE type = 'table' AND name NOT LIKE 'sqlite_%' AND sql IS NOT NULL
+         ORDER BY name",
+    )?;
+    let tables: Vec<(String, String)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<_>>()?;
+
+    for (table, create_sql) in &tables {
+        dump.push_str(&format!("{};\n", create_sql));
+
+        let mut rows_stmt = conn.prepare(&format!("SELECT * FROM \"{}\"", table))?;
+        let column_count = rows_stmt.column_count();
+        let mut rows = rows_stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            let values = (0..column_count)
+                .map(|i| row.get_ref(i).map(sql_literal))
+                .collect::<Result<Vec<_>>>()?;
+            dump.push_str(&format!("INSERT INTO \"{}\" VALUES ({});\n", table, values.join(", ")));
+        }
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT sql FROM sqlite_master WHERE type = 'index' AND sql IS NOT NULL ORDER BY name",
+    )?;
+    for index_sql in stmt.query_map([], |row| row.get::<_, String>(0))? {
+        dump.push_str(&format!("{};\n", index_sql?));
+    }
+
+    dump.push_str("COMMIT;\n");
+    Ok(dump)
+}
+
+fn database_size(conn: &Connection) -> Result<i64> {
+    conn.query_row(
+        "SELECT page_count * page_size FROM pragma_page_count(), pragma_page_size()",
+        [],
+        |row| row.get(0),
+    )
+}
+
+/// Runs `VACUUM` and `PRAGMA optimize`, returning the database size in bytes
+/// before and after.
+pub fn optimize_database(conn: &Connection) -> Result<(i64, i64)> {
+    let before = database_size(conn)?;
+    conn.execute_batch("VACUUM; PRAGMA optimize;")?;
+    let after = database_size(conn)?;
+    Ok((before, after))
+}
+
+/// Deletes every stored draw and recorded drawless date in one transaction,
+/// then `VACUUM`s, keeping the schema. Does nothing unless `confirm` is true.
+/// Returns how many draws were deleted.
+pub fn clear_all_data(conn: &Connection, confirm: bool) -> Result<usize> {
+    if !confirm {
+        return Ok(0);
+    }
+    let tx = conn.unchecked_transaction()?;
+    let deleted = tx.execute("DELETE FROM lottery_results", [])?;
+    tx.execute("DELETE FROM empty_draw_dates", [])?;
+    tx.commit()?;
+    conn.execute_batch("VACUUM")?;
+    Ok(deleted)
+}
+
+/// Copies the database to `dest_path` with SQLite's online backup API, which
+/// stays consistent even if another connection writes during the copy.
+pub fn backup_database(conn: &Connection, dest_path: &str) -> Result<()> {
+    let mut dest = Connection::open(dest_path)?;
+    let backup = Backup::new(conn, &mut dest)?;
+    backup.run_to_completion(100, Duration::from_millis(10), None)
+}
@@ -0,0 +1,68 @@
+use rusqlite::{Connection, Result};
+
+/// Schema changes in the order they were introduced. Append new entries;
+/// never edit one that has shipped, as existing databases have already run it.
+const MIGRATIONS: &[(i64, &str)] = &[
+    (
+        1,
+        "CREATE TABLE IF NOT EXISTS lottery_results (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            draw_date TEXT NOT NULL,
+            draw_no TEXT NOT NULL,
+            first_prize TEXT,
+            last_two_digits TEXT,
+            last_three_digits TEXT,
+            near_first TEXT,
+            second_prize TEXT,
+            third_prize TEXT,
+            fourth_prize TEXT,
+            fifth_prize TEXT,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )",
+    ),
+    (
+        2,
+        "CREATE TABLE IF NOT EXISTS empty_draw_dates (
+            draw_date TEXT PRIMARY KEY,
+            checked_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )",
+    ),
+    (3, "ALTER TABLE lottery_results ADD COLUMN raw_json TEXT"),
+    (
+        4,
+        "UPDATE lottery_results SET created_at = strftime('%Y-%m-%dT%H:%M:%SZ', created_at)
+         WHERE created_at NOT LIKE '%Z' AND strftime('%Y-%m-%dT%H:%M:%SZ', created_at) IS NOT NULL",
+    ),
+];
+
+/// The highest migration version applied to the database, or 0 for none.
+pub fn schema_version(conn: &Connection) -> Result<i64> {
+    conn.query_row(
+        "SELECT COALESCE(MAX(version), 0) FROM schema_migrations",
+        [],
+        |row| row.get(0),
+    )
+}
+
+/// Runs every migration newer than the database's schema version, each in its
+/// own transaction, and returns how many were applied.
+pub fn apply_migrations(conn: &Connection) -> Result<usize> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS schema_migrations (
+            version INTEGER PRIMARY KEY,
+            applied_at DATETIME DEFAULT CURRENT_TIMESTAMP
+        )",
+        [],
+    )?;
+
+    let current = schema_version(conn)?;
+    let mut applied = 0;
+    for (version, sql) in MIGRATIONS.iter().filter(|(version, _)| *version > current) {
+        let tx = conn.unchecked_transaction()?;
+        tx.execute_batch(sql)?;
+        tx.execute("INSERT INTO schema_migrations (version) VALUES (?1)", [version])?;
+        tx.commit()?;
+        applied += 1;
+    }
+    Ok(applied)
+}
@@ -0,0 +1,22 @@
+use crate::report::report_filename;
+use crate::utils::parse_draw_date;
+use image::{ImageFormat, Luma};
+use qrcode::QrCode;
+use std::error::Error;
+use std::io::Cursor;
+
+/// Renders a PNG QR code pointing at the draw's report file under `base_url`.
+pub fn generate_report_qr(date: &str, base_url: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+    parse_draw_date(date).map_err(|e| format!("Invalid date {}: {}", date, e))?;
+    if !(base_url.starts_with("http://") || base_url.starts_with("https://")) {
+        return Err(format!("Invalid base URL {}: expected http:// or https://", base_url).into());
+    }
+
+    let url = format!("{}/{}", base_url.trim_end_matches('/'), report_filename(date));
+    let code = QrCode::new(url.as_bytes())?;
+    let image = code.render::<Luma<u8>>().build();
+
+    let mut png = Vec::new();
+    image.write_to(&mut Cursor::new(&mut png), ImageFormat::Png)?;
+    Ok(png)
+}
@@ -0,0 +1,322 @@
+use crate::analysis::{digit_position_frequency, last2_counts, overdue_numbers};
+use crate::database::{
+    get_all_lottery_results, get_database_stats, get_lottery_by_date, get_lottery_results_by_date_range,
+};
+use crate::types::{Category, Language, LotteryData, SortOrder};
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use rusqlite::Connection;
+use std::error::Error;
+use std::fs;
+use std::collections::BTreeSet;
+use std::io::{self, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use zip::ZipWriter;
+use zip::write::SimpleFileOptions;
+
+pub const DEFAULT_REPORT_DIR: &str = "reports";
+
+pub fn report_filename(date: &str) -> String {
+    format!("lottery_report_{}.txt", date)
+}
+
+/// The draw date of a report file name, plain or gzipped.
+pub fn report_date_from_filename(filename: &str) -> Option<&str> {
+    let rest = filename.strip_prefix("lottery_report_")?;
+    rest.strip_suffix(".txt").or_else(|| rest.strip_suffix(".txt.gz"))
+}
+
+/// Report files in `dir` (plain and gzipped), sorted by name. A missing
+/// directory has no reports.
+pub fn list_generated_files(dir: &str) -> io::Result<Vec<String>> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let mut files = Vec::new();
+    for entry in entries {
+        let filename = entry?.file_name().to_string_lossy().to_string();
+        if report_date_from_filename(&filename).is_some() {
+            files.push(filename);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+#[derive(Debug, Default)]
+pub struct ReportAudit {
+    /// Report files whose draw is no longer stored.
+    pub stale: Vec<String>,
+    /// Stored draw dates with no report file, oldest first.
+    pub missing: Vec<String>,
+}
+
+/// Compares the reports in `dir` with the stored draws in both directions.
+pub fn audit_reports(conn: &Connection, dir: &str) -> Result<ReportAudit, Box<dyn Error>> {
+    let stored: BTreeSet<String> = get_all_lottery_results(conn)?.into_iter().map(|data| data.draw_date).collect();
+    let files = list_generated_files(dir)?;
+    let reported: BTreeSet<&str> = files.iter().filter_map(|f| report_date_from_filename(f)).collect();
+
+    Ok(ReportAudit {
+        stale: files
+            .iter()
+            .filter(|f| report_date_from_filename(f).is_some_and(|date| !stored.contains(date)))
+            .cloned()
+            .collect(),
+        missing: stored.iter().filter(|date| !reported.contains(date.as_str())).cloned().collect(),
+    })
+}
+
+/// A plain-text summary of a draw, one prize category per line, with every
+/// label in `lang`.
+pub fn generate_report(data: &LotteryData, lang: Language) -> String {
+    let (date_label, draw_no_label) = match lang {
+        Language::Th => ("งวดวันที่", "งวดที่"),
+        Language::En => ("Draw Date", "Draw No"),
+    };
+    let mut report = format!("{}: {}\n{}: {}\n", date_label, data.draw_date, draw_no_label, data.draw_no);
+    for (category, numbers) in data.prize_categories() {
+        if !numbers.is_empty() {
+            report.push_str(&format!("{}: {}\n", category.display_name(lang), numbers.join(", ")));
+        }
+    }
+    report
+}
+
+#[derive(Debug, Clone)]
+pub struct ReportOptions {
+    pub dir: String,
+    /// Gzip the report and add a `.gz` suffix.
+    pub compress: bool,
+    /// Regenerate reports that already exist instead of skipping them. Only
+    /// those whose content changed are rewritten.
+    pub force: bool,
+    pub lang: Language,
+}
+
+impl Default for ReportOptions {
+    fn default() -> Self {
+        ReportOptions {
+            dir: DEFAULT_REPORT_DIR.to_string(),
+            compress: false,
+            force: false,
+            lang: Language::default(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ReportOutcome {
+    Written(PathBuf),
+    /// The file already held the same report, so it was not rewritten.
+    Unchanged(PathBuf),
+    /// A report for the date already existed and `force` was not set.
+    Skipped(PathBuf),
+    NoDraw,
+}
+
+/// Path of an existing report for `date` in `dir`, plain or gzipped.
+pub fn existing_report(dir: &str, date: &str) -> Option<PathBuf> {
+    let plain = Path::new(dir).join(report_filename(date));
+    let gzipped = plain.with_extension("txt.gz");
+    [plain, gzipped].into_iter().find(|path| path.exists())
+}
+
+/// Writes the report for a stored draw, or returns `NoDraw` if none is stored
+/// for the date. A file that already holds the same report is left untouched
+/// and reported as `Unchanged`; see also `generate_report_if_missing`.
+pub fn generate_and_save_report(
+    conn: &Connection,
+    date: &str,
+    options: &ReportOptions,
+) -> Result<ReportOutcome, Box<dyn Error>> {
+    match get_lottery_by_date(conn, date)? {
+        Some(data) => save_report(&data, options),
+        None => Ok(ReportOutcome::NoDraw),
+    }
+}
+
+/// Like `generate_and_save_report`, but leaves an existing report alone
+/// unless `options.force` is set.
+pub fn generate_report_if_missing(
+    conn: &Connection,
+    date: &str,
+    options: &ReportOptions,
+) -> Result<ReportOutcome, Box<dyn Error>> {
+    if !options.force
+        && let Some(path) = existing_report(&options.dir, date)
+    {
+        return Ok(ReportOutcome::Skipped(path));
+    }
+    generate_and_save_report(conn, date, options)
+}
+
+/// The text of a saved report, gunzipped if needed, or `None` if it cannot be read.
+fn read_report_file(path: &Path) -> Option<String> {
+    let file = fs::File::open(path).ok()?;
+    let mut report = String::new();
+    if path.extension().is_some_and(|ext| ext == "gz") {
+        GzDecoder::new(file).read_to_string(&mut report).ok()?;
+    } else {
+        BufReader::new(file).read_to_string(&mut report).ok()?;
+    }
+    Some(report)
+}
+
+fn save_report(data: &LotteryData, options: &ReportOptions) -> Result<ReportOutcome, Box<dyn Error>> {
+    fs::create_dir_all(&options.dir)?;
+    let report = generate_report(data, options.lang);
+    let mut path = Path::new(&options.dir).join(report_filename(&data.draw_date));
+    let existing = if options.compress { path.with_extension("txt.gz") } else { path.clone() };
+    if read_report_file(&existing).as_deref() == Some(report.as_str()) {
+        return Ok(ReportOutcome::Unchanged(existing));
+    }
+    write_report_file(&mut path, &report, options.compress)?;
+    Ok(ReportOutcome::Written(path))
+}
+
+/// Writes `report` to `path`, or gzipped to `path` with a `.gz` suffix.
+fn write_report_file(path: &mut PathBuf, report: &str, compress: bool) -> io::Result<()> {
+    if compress {
+        path.set_extension("txt.gz");
+        let mut encoder = GzEncoder::new(fs::File::create(&path)?, Compression::default());
+        encoder.write_all(report.as_bytes())?;
+        encoder.finish()?;
+    } else {
+        fs::write(&path, report)?;
+    }
+    Ok(())
+}
+
+/// Generates reports for every draw stored between the two dates, oldest
+/// first, skipping existing ones unless `options.force` is set.
+pub fn generate_reports_for_range(
+    conn: &Connection,
+    start_date: &str,
+    end_date: &str,
+    options: &ReportOptions,
+) -> Result<Vec<ReportOutcome>, Box<dyn Error>> {
+    let mut outcomes = Vec::new();
+    for data in &get_lottery_results_by_date_range(conn, start_date, end_date, SortOrder::Asc)? {
+        match existing_report(&options.dir, &data.draw_date) {
+            Some(path) if !options.force => outcomes.push(ReportOutcome::Skipped(path)),
+            _ => outcomes.push(save_report(data, options)?),
+        }
+    }
+    Ok(outcomes)
+}
+
+pub const STATS_REPORT_FILENAME: &str = "lottery_stats.txt";
+
+/// A plain-text summary across all stored draws: coverage, the most frequent
+/// and most overdue last-2 numbers, first-prize leading digits, and a bar per
+/// tens digit of the last-2 prize.
+pub fn generate_stats_report(conn: &Connection) -> Result<String, Box<dyn Error>> {
+    let stats = get_database_stats(conn)?;
+    let mut report = format!("Total draws: {}\n", stats.total_draws);
+    if stats.total_draws == 0 {
+        return Ok(report);
+    }
+    if let (Some(first), Some(last)) = (&stats.first_draw, &stats.last_draw) {
+        report.push_str(&format!("Coverage: {} to {}\n", first, last));
+    }
+
+    let counts = last2_counts(conn)?;
+    let mut by_count: Vec<(usize, u32)> = counts.iter().copied().enumerate().collect();
+    by_count.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    let hot: Vec<String> = by_count
+        .iter()
+        .filter(|(_, count)| *count > 0)
+        .take(5)
+        .map(|(ending, count)| format!("{:02} ({})", ending, count))
+        .collect();
+    report.push_str(&format!("\nMost frequent last 2: {}\n", hot.join(", ")));
+
+    let cold: Vec<String> = overdue_numbers(conn, 2)?
+        .into_iter()
+        .take(5)
+        .map(|(ending, _, draws_ago)| format!("{} ({} draws)", ending, draws_ago))
+        .collect();
+    report.push_str(&format!("Most overdue last 2: {}\n", cold.join(", ")));
+
+    if let Some(leading) = digit_position_frequency(conn, Category::First)?.first()
+        && let Some((digit, count)) = leading.iter().enumerate().max_by_key(|&(digit, count)| (*count, std::cmp::Reverse(digit)))
+        && *count > 0
+    {
+        report.push_str(&format!("Most common first-prize leading digit: {} ({} draws)\n", digit, count));
+    }
+
+    report.push_str("\nLast 2 by tens digit:\n");
+    let mut by_tens = [0u32; 10];
+    for (ending, count) in counts.iter().enumerate() {
+        by_tens[ending / 10] += count;
+    }
+    // Bars are scaled so the longest is 50 characters.
+    let longest = by_tens.iter().copied().max().unwrap_or(0).max(1);
+    for (tens, count) in by_tens.iter().enumerate() {
+        let bar = "#".repeat((count * 50 / longest) as usize);
+        report.push_str(&format!("{}x | {} {}\n", tens, bar, count));
+    }
+    Ok(report)
+}
+
+/// Writes `generate_stats_report` to `options.dir`, gzipped if
+/// `options.compress` is set, and returns its path. Always rewrites.
+pub fn generate_and_save_stats_report(conn: &Connection, options: &ReportOptions) -> Result<PathBuf, Box<dyn Error>> {
+    fs::create_dir_all(&options.dir)?;
+    let report = generate_stats_report(conn)?;
+    let mut path = Path::new(&options.dir).join(STATS_REPORT_FILENAME);
+    write_report_file(&mut path, &report, options.compress)?;
+    Ok(path)
+}
+
+/// Packages the report of every draw stored in `year`, plus an `index.txt`
+/// listing them, into one zip at `dest`. Returns how many draws it holds.
+pub fn export_year_bundle(conn: &Connection, year: &str, dest: &str, lang: Language) -> Result<usize, Box<dyn Error>> {
+    let (start_date, end_date) = (format!("{}-01-01", year), format!("{}-12-31", year));
+    let draws = get_lottery_results_by_date_range(conn, &start_date, &end_date, SortOrder::Asc)?;
+    let mut zip = ZipWriter::new(fs::File::create(dest)?);
+    let options = SimpleFileOptions::default();
+
+    let mut index = format!("Lottery reports for {}\n\n", year);
+    for data in &draws {
+        let filename = report_filename(&data.draw_date);
+        index.push_str(&format!("{}  {}\n", data.draw_date, filename));
+        zip.start_file(filename, options)?;
+        zip.write_all(generate_report(data, lang).as_bytes())?;
+    }
+    zip.start_file("index.txt", options)?;
+    zip.write_all(index.as_bytes())?;
+    zip.finish()?;
+    Ok(draws.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::{create_in_memory_database, save_lottery_result};
+
+    #[test]
+    fn forced_regeneration_leaves_unchanged_reports_alone() {
+        let conn = create_in_memory_database().unwrap();
+        let data: LotteryData =
+            serde_json::from_str(r#"{"drawDate": "2024-03-01", "drawNo": "5", "first": "123456", "last2": "56"}"#)
+                .unwrap();
+        save_lottery_result(&conn, &data).unwrap();
+        let dir = std::env::temp_dir().join(format!("lotto-report-test-{}", std::process::id()));
+        let mut options = ReportOptions { dir: dir.to_string_lossy().to_string(), force: true, ..Default::default() };
+
+        let first = generate_report_if_missing(&conn, "2024-03-01", &options).unwrap();
+        let again = generate_report_if_missing(&conn, "2024-03-01", &options).unwrap();
+        options.lang = Language::En;
+        let relabelled = generate_report_if_missing(&conn, "2024-03-01", &options).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(matches!(first, ReportOutcome::Written(_)));
+        assert!(matches!(again, ReportOutcome::Unchanged(_)));
+        assert!(matches!(relabelled, ReportOutcome::Written(_)));
+    }
+}
@@ -0,0 +1,70 @@
+use crate::api::{fetch_draw, parse_draw_response};
+use crate::config::Config;
+use crate::types::LotteryData;
+use crate::utils::format_draw_date;
+use async_trait::async_trait;
+use std::error::Error;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Where fetched draws come from. The fetch-and-save functions in `api` take
+/// any source, so they can run against GLO, saved files or a stub.
+#[async_trait(?Send)]
+pub trait LotterySource {
+    /// The draw on a GLO `(date, month, year)` with the raw payload it was
+    /// parsed from, or `None` if there was no draw.
+    async fn fetch(&self, date: &str, month: &str, year: &str) -> Result<Option<(LotteryData, String)>, Box<dyn Error>>;
+
+    /// Whether batch fetches should pause `request_delay` after each request.
+    /// Only network sources need the pause.
+    fn is_rate_limited(&self) -> bool {
+        true
+    }
+}
+
+/// The live GLO API.
+#[derive(Debug, Clone)]
+pub struct GloSource {
+    pub timeout: Duration,
+}
+
+impl GloSource {
+    pub fn new(config: &Config) -> Self {
+        GloSource { timeout: config.request_timeout }
+    }
+}
+
+#[async_trait(?Send)]
+impl LotterySource for GloSource {
+    async fn fetch(&self, date: &str, month: &str, year: &str) -> Result<Option<(LotteryData, String)>, Box<dyn Error>> {
+        fetch_draw(date, month, year, self.timeout).await
+    }
+}
+
+/// Saved GLO responses named `YYYY-MM-DD.json` in one directory. A date with
+/// no file is an error rather than a drawless date, so a partial directory
+/// never records real draw dates as empty.
+#[derive(Debug, Clone)]
+pub struct FileSource {
+    pub dir: PathBuf,
+}
+
+#[async_trait(?Send)]
+impl LotterySource for FileSource {
+    async fn fetch(&self, date: &str, month: &str, year: &str) -> Result<Option<(LotteryData, String)>, Box<dyn Error>> {
+        let path = self.dir.join(format!("{}.json", format_draw_date(date, month, year)));
+        match fs::read_to_string(&path) {
+            Ok(raw_json) => parse_draw_response(raw_json),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                Err(format!("No saved response at {}", path.display()).into())
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn is_rate_limited(&self) -> bool {
+        false
+    }
+}
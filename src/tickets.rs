@@ -0,0 +1,94 @@
+use crate::database::get_lottery_by_date;
+use crate::integrity::is_valid_number;
+use crate::types::Category;
+use crate::utils::adjacent_numbers;
+use rusqlite::{Connection, Result};
+use serde::Serialize;
+
+#[derive(Serialize, Debug)]
+pub struct PrizeMatch {
+    pub category: Category,
+    /// The prize number the ticket matched, e.g. `56` for a last-2 win.
+    pub number: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct TicketCheck {
+    pub ticket: String,
+    pub matches: Vec<PrizeMatch>,
+}
+
+/// Checks six-digit tickets against the draw on `date`, or returns `None` if
+/// no draw is stored. The last-2 and last-3 prizes match on the ticket's
+/// ending, every other category on the whole number. When the draw has no
+/// near-first list, the numbers either side of the first prize are used.
+/// Tickets that are not six digits match nothing.
+pub fn check_tickets(conn: &Connection, date: &str, tickets: &[String]) -> Result<Option<Vec<TicketCheck>>> {
+    let Some(data) = get_lottery_by_date(conn, date)? else {
+        return Ok(None);
+    };
+    let mut categories = data.prize_categories();
+    // Older imports may lack the near-first list; the first prize fixes it anyway.
+    if let Some((_, near_first)) = categories.iter_mut().find(|(category, _)| *category == Category::Near1)
+        && near_first.is_empty()
+        && let Some((below, above)) = data.first_prize.as_deref().and_then(adjacent_numbers)
+    {
+        *near_first = vec![below, above];
+    }
+
+    Ok(Some(
+        tickets
+            .iter()
+            .map(|ticket| {
+                let matches = if is_valid_number(Category::First, ticket) {
+                    categories
+                        .iter()
+                        .flat_map(|(category, numbers)| {
+                            numbers
+                                .iter()
+                                .filter(move |number| match category {
+                                    Category::Last2 | Category::Last3 => ticket.ends_with(number.as_str()),
+                                    _ => ticket == *number,
+                                })
+                                .map(|number| PrizeMatch { category: *category, number: number.clone() })
+                        })
+                        .collect()
+                } else {
+                    Vec::new()
+                };
+                TicketCheck { ticket: ticket.clone(), matches }
+            })
+            .collect(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::{create_in_memory_database, save_lottery_result};
+    use crate::types::LotteryData;
+
+    #[test]
+    fn near_first_is_derived_when_the_draw_has_no_list() {
+        let conn = create_in_memory_database().unwrap();
+        let data = LotteryData {
+            draw_date: "2024-03-01".to_string(),
+            draw_no: "5".to_string(),
+            first_prize: Some("999999".to_string()),
+            last_two_digits: None,
+            last_three_digits: None,
+            near_first: None,
+            second_prize: None,
+            third_prize: None,
+            fourth_prize: None,
+            fifth_prize: None,
+        };
+        save_lottery_result(&conn, &data).unwrap();
+
+        let tickets = ["000000".to_string(), "999998".to_string(), "123456".to_string()];
+        let checks = check_tickets(&conn, "2024-03-01", &tickets).unwrap().unwrap();
+        let categories: Vec<Vec<Category>> =
+            checks.iter().map(|check| check.matches.iter().map(|m| m.category).collect()).collect();
+        assert_eq!(categories, [vec![Category::Near1], vec![Category::Near1], vec![]]);
+    }
+}
@@ -0,0 +1,316 @@
+use rusqlite::Row;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::fmt;
+use std::str::FromStr;
+
+/// A GLO prize category. `as_str` is the name used by the API and in JSON.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Category {
+    First,
+    Last2,
+    Last3,
+    Near1,
+    Second,
+    Third,
+    Fourth,
+    Fifth,
+}
+
+impl Category {
+    pub const ALL: [Category; 8] = [
+        Category::First,
+        Category::Last2,
+        Category::Last3,
+        Category::Near1,
+        Category::Second,
+        Category::Third,
+        Category::Fourth,
+        Category::Fifth,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Category::First => "first",
+            Category::Last2 => "last2",
+            Category::Last3 => "last3",
+            Category::Near1 => "near1",
+            Category::Second => "second",
+            Category::Third => "third",
+            Category::Fourth => "fourth",
+            Category::Fifth => "fifth",
+        }
+    }
+
+    /// The `lottery_results` column the category is stored in.
+    pub fn column(&self) -> &'static str {
+        match self {
+            Category::First => "first_prize",
+            Category::Last2 => "last_two_digits",
+            Category::Last3 => "last_three_digits",
+            Category::Near1 => "near_first",
+            Category::Second => "second_prize",
+            Category::Third => "third_prize",
+            Category::Fourth => "fourth_prize",
+            Category::Fifth => "fifth_prize",
+        }
+    }
+
+    pub fn display_name_th(&self) -> &'static str {
+        match self {
+            Category::First => "รางวัลที่ 1",
+            Category::Last2 => "เลขท้าย 2 ตัว",
+            Category::Last3 => "เลขท้าย 3 ตัว",
+            Category::Near1 => "รางวัลข้างเคียงรางวัลที่ 1",
+            Category::Second => "รางวัลที่ 2",
+            Category::Third => "รางวัลที่ 3",
+            Category::Fourth => "รางวัลที่ 4",
+            Category::Fifth => "รางวัลที่ 5",
+        }
+    }
+
+    pub fn display_name_en(&self) -> &'static str {
+        match self {
+            Category::First => "First Prize",
+            Category::Last2 => "Last 2 Digits",
+            Category::Last3 => "Last 3 Digits",
+            Category::Near1 => "Near First Prize",
+            Category::Second => "Second Prize",
+            Category::Third => "Third Prize",
+            Category::Fourth => "Fourth Prize",
+            Category::Fifth => "Fifth Prize",
+        }
+    }
+
+    pub fn display_name(&self, lang: Language) -> &'static str {
+        match lang {
+            Language::Th => self.display_name_th(),
+            Language::En => self.display_name_en(),
+        }
+    }
+
+    /// How many numbers GLO draws for the category.
+    pub fn expected_count(&self) -> usize {
+        match self {
+            Category::First | Category::Last2 => 1,
+            Category::Near1 => 2,
+            Category::Last3 => 4,
+            Category::Second => 5,
+            Category::Third => 10,
+            Category::Fourth => 50,
+            Category::Fifth => 100,
+        }
+    }
+
+    pub fn digits(&self) -> usize {
+        match self {
+            Category::Last2 => 2,
+            Category::Last3 => 3,
+            _ => 6,
+        }
+    }
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for Category {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Category::ALL
+            .into_iter()
+            .find(|category| category.as_str() == s)
+            .ok_or_else(|| format!("Unknown prize category {}", s))
+    }
+}
+
+/// The expected count, digit length and Thai display name of every category,
+/// for validating and explaining draws.
+pub fn prize_structure_template() -> Value {
+    Value::Array(
+        Category::ALL
+            .iter()
+            .map(|category| {
+                json!({
+                    "category": category.as_str(),
+                    "name": category.display_name_th(),
+                    "count": category.expected_count(),
+                    "digits": category.digits(),
+                })
+            })
+            .collect(),
+    )
+}
+
+/// The language of report labels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Language {
+    #[default]
+    Th,
+    En,
+}
+
+impl FromStr for Language {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "th" => Ok(Language::Th),
+            "en" => Ok(Language::En),
+            _ => Err(format!("Invalid language {}: expected th or en", s)),
+        }
+    }
+}
+
+/// How a searched number is compared against prize numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchMode {
+    /// The prize number is exactly the searched number.
+    Exact,
+    /// The prize number ends with the searched number.
+    Suffix,
+}
+
+impl MatchMode {
+    pub fn matches(&self, prize_number: &str, number: &str) -> bool {
+        match self {
+            MatchMode::Exact => prize_number == number,
+            MatchMode::Suffix => prize_number.ends_with(number),
+        }
+    }
+}
+
+impl FromStr for MatchMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "exact" => Ok(MatchMode::Exact),
+            "suffix" => Ok(MatchMode::Suffix),
+            _ => Err(format!("Invalid match mode {}: expected exact or suffix", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+impl SortOrder {
+    pub fn as_sql(&self) -> &'static str {
+        match self {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        }
+    }
+}
+
+impl FromStr for SortOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "asc" => Ok(SortOrder::Asc),
+            "desc" => Ok(SortOrder::Desc),
+            _ => Err(format!("Invalid sort order {}: expected asc or desc", s)),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct LotteryRequest {
+    pub date: String,
+    pub month: String,
+    pub year: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct LotteryResponse {
+    pub status: Value,
+    pub data: Option<LotteryData>,
+}
+
+impl LotteryResponse {
+    /// GLO reports success as either `"success"` or `true`.
+    pub fn is_success(&self) -> bool {
+        self.status == "success" || self.status == true
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LotteryData {
+    #[serde(rename = "drawDate")]
+    pub draw_date: String,
+    #[serde(rename = "drawNo")]
+    pub draw_no: String,
+    #[serde(rename = "first")]
+    pub first_prize: Option<String>,
+    #[serde(rename = "last2")]
+    pub last_two_digits: Option<String>,
+    #[serde(rename = "last3")]
+    pub last_three_digits: Option<Vec<String>>,
+    #[serde(rename = "near1")]
+    pub near_first: Option<Vec<String>>,
+    #[serde(rename = "second")]
+    pub second_prize: Option<Vec<String>>,
+    #[serde(rename = "third")]
+    pub third_prize: Option<Vec<String>>,
+    #[serde(rename = "fourth")]
+    pub fourth_prize: Option<Vec<String>>,
+    #[serde(rename = "fifth")]
+    pub fifth_prize: Option<Vec<String>>,
+}
+
+/// A comma-joined prize column back as its numbers. An empty list is stored as
+/// an empty string and comes back empty rather than as one blank number.
+fn split_numbers(value: Option<String>) -> Option<Vec<String>> {
+    value.map(|v| v.split(',').filter(|s| !s.is_empty()).map(|s| s.to_string()).collect())
+}
+
+impl LotteryData {
+    /// Rebuilds a draw from a `lottery_results` row selected as `draw_date,
+    /// draw_no` followed by the eight prize columns; the inverse of
+    /// `save_lottery_result`.
+    pub fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Ok(LotteryData {
+            draw_date: row.get(0)?,
+            draw_no: row.get(1)?,
+            first_prize: row.get(2)?,
+            last_two_digits: row.get(3)?,
+            last_three_digits: split_numbers(row.get(4)?),
+            near_first: split_numbers(row.get(5)?),
+            second_prize: split_numbers(row.get(6)?),
+            third_prize: split_numbers(row.get(7)?),
+            fourth_prize: split_numbers(row.get(8)?),
+            fifth_prize: split_numbers(row.get(9)?),
+        })
+    }
+
+    pub fn numbers(&self, category: Category) -> Vec<String> {
+        match category {
+            Category::First => self.first_prize.iter().cloned().collect(),
+            Category::Last2 => self.last_two_digits.iter().cloned().collect(),
+            Category::Last3 => self.last_three_digits.clone().unwrap_or_default(),
+            Category::Near1 => self.near_first.clone().unwrap_or_default(),
+            Category::Second => self.second_prize.clone().unwrap_or_default(),
+            Category::Third => self.third_prize.clone().unwrap_or_default(),
+            Category::Fourth => self.fourth_prize.clone().unwrap_or_default(),
+            Category::Fifth => self.fifth_prize.clone().unwrap_or_default(),
+        }
+    }
+
+    /// Every prize category of the draw with its numbers, in `Category::ALL` order.
+    pub fn prize_categories(&self) -> Vec<(Category, Vec<String>)> {
+        Category::ALL
+            .into_iter()
+            .map(|category| (category, self.numbers(category)))
+            .collect()
+    }
+}
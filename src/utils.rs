@@ -0,0 +1,135 @@
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, Utc};
+use std::fmt;
+use std::str::FromStr;
+
+/// Draws are held on the 1st and 16th of every month. Returns `(date, month, year)`
+/// triples in the shape the GLO API expects.
+pub fn generate_lottery_dates(year: &str) -> Vec<(String, String, String)> {
+    let mut dates = Vec::new();
+    for month in 1..=12 {
+        for day in ["01", "16"] {
+            dates.push((day.to_string(), format!("{:02}", month), year.to_string()));
+        }
+    }
+    dates
+}
+
+/// The most recent scheduled draw date (the 1st or 16th) on or before `today`.
+pub fn latest_scheduled_draw(today: NaiveDate) -> NaiveDate {
+    let day = if today.day() >= 16 { 16 } else { 1 };
+    today.with_day(day).unwrap_or(today)
+}
+
+/// The numbers one below and one above `number`, keeping its width and
+/// wrapping around, so `000000` gives `999999` and `000001`. `None` unless
+/// `number` is all digits.
+pub fn adjacent_numbers(number: &str) -> Option<(String, String)> {
+    if number.is_empty() || !number.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let width = number.len() as u32;
+    let modulus = 10u64.checked_pow(width)?;
+    let value: u64 = number.parse().ok()?;
+    let below = (value + modulus - 1) % modulus;
+    let above = (value + 1) % modulus;
+    let width = width as usize;
+    Some((format!("{:0width$}", below), format!("{:0width$}", above)))
+}
+
+/// Joins a GLO `(date, month, year)` triple into the stored `YYYY-MM-DD` form.
+pub fn format_draw_date(date: &str, month: &str, year: &str) -> String {
+    format!("{}-{}-{}", year, month, date)
+}
+
+/// Splits a `YYYY-MM-DD` date into the GLO `(date, month, year)` triple,
+/// rejecting anything that is not a real calendar date.
+pub fn split_draw_date(draw_date: &str) -> Result<(String, String, String), chrono::ParseError> {
+    let date = parse_draw_date(draw_date)?;
+    Ok((format!("{:02}", date.day()), format!("{:02}", date.month()), date.year().to_string()))
+}
+
+pub fn parse_draw_date(date: &str) -> Result<NaiveDate, chrono::ParseError> {
+    NaiveDate::parse_from_str(date, "%Y-%m-%d")
+}
+
+pub fn draw_date_year(date: &str) -> Result<i32, chrono::ParseError> {
+    parse_draw_date(date).map(|d| d.year())
+}
+
+/// The last calendar day of a month, accounting for leap years.
+pub fn last_day_of_month(year: i32, month: u32) -> Option<NaiveDate> {
+    let (next_year, next_month) = if month == 12 { (year + 1, 1) } else { (year, month + 1) };
+    NaiveDate::from_ymd_opt(next_year, next_month, 1)?.pred_opt()
+}
+
+/// A stored `created_at` timestamp: RFC 3339, or SQLite's zoneless
+/// `CURRENT_TIMESTAMP` format, which is UTC.
+pub fn parse_created_at(created_at: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(created_at)
+        .map(|time| time.with_timezone(&Utc))
+        .or_else(|_| NaiveDateTime::parse_from_str(created_at, "%Y-%m-%d %H:%M:%S").map(|time| time.and_utc()))
+        .ok()
+}
+
+/// A stored `YYYY-MM-DD` draw date, parsed once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DrawDate(NaiveDate);
+
+impl DrawDate {
+    pub fn parse(date: &str) -> Result<Self, chrono::ParseError> {
+        parse_draw_date(date).map(DrawDate)
+    }
+
+    pub fn date(&self) -> NaiveDate {
+        self.0
+    }
+
+    pub fn year(&self) -> i32 {
+        self.0.year()
+    }
+
+    pub fn month(&self) -> u32 {
+        self.0.month()
+    }
+
+    pub fn day(&self) -> u32 {
+        self.0.day()
+    }
+
+    /// The draw's position in the year (1..=24), assuming the usual 1st/16th
+    /// schedule. Only a guess: GLO occasionally moves a draw by a day or two.
+    pub fn period_guess(&self) -> u32 {
+        let half = if self.day() < 16 { 1 } else { 2 };
+        (self.month() - 1) * 2 + half
+    }
+}
+
+impl FromStr for DrawDate {
+    type Err = chrono::ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        DrawDate::parse(s)
+    }
+}
+
+impl fmt::Display for DrawDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.format("%Y-%m-%d"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn last_day_of_february_follows_leap_years() {
+        assert_eq!(last_day_of_month(2024, 2), NaiveDate::from_ymd_opt(2024, 2, 29));
+        assert_eq!(last_day_of_month(2025, 2), NaiveDate::from_ymd_opt(2025, 2, 28));
+    }
+
+    #[test]
+    fn last_day_of_december_rolls_into_next_year() {
+        assert_eq!(last_day_of_month(2024, 12), NaiveDate::from_ymd_opt(2024, 12, 31));
+    }
+}